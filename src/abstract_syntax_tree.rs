@@ -1,9 +1,11 @@
-use std::borrow::{Borrow, BorrowMut};
-use crate::abstract_syntax_tree::Statement::{VariableDeclaration, ReturnStatement};
+use crate::abstract_syntax_tree::StatementKind::{VariableDeclaration, ReturnStatement};
 use std::rc::Rc;
 use std::cell::{RefCell};
-use crate::executor::{Variable, Callable};
+use crate::executor::{Variable, Callable, Environment, get_array_dump, is_stack_exhausted};
 use std::ops::Deref;
+use crate::operators::{OperatorTable, Operator, OperatorHook, Associativity, Arity, get_declared_priority, get_declared_symbol, is_valid_custom_symbol};
+use crate::lexer::{Token, TokenKind, Span};
+use crate::diagnostic::Diagnostic;
 
 pub struct Tuple {
     pub expressions: Vec<Expression>
@@ -27,13 +29,20 @@ impl Dumpable for Tuple {
 
 pub struct Function {
     pub args: Vec<String>,
-    pub scope: Rc<RefCell<Scope>>
+    pub scope: Rc<Scope>,
+    /// The environment the function was created in, captured when the `function` expression is evaluated. The parsed
+    /// function has none.
+    pub closure: Option<Rc<RefCell<Environment>>>
 }
 
 pub enum ExpressionType {
     Undefined,
     Value,
     Operation,
+    /// `{ name: value, ... }`, the value being a tuple of `name = value` operations creating the members in order.
+    Object,
+    /// `[a, b, ...]`, the value being a tuple of the elements.
+    Array,
 }
 
 impl Copy for ExpressionType {}
@@ -49,174 +58,57 @@ pub struct Expression {
     pub left: Option<Box<Expression>>,
     pub right: Option<Box<Expression>>,
     pub value: Option<Value>,
-    pub operator: Option<String>,
+    pub operator: Option<Operator>,
+    pub span: Option<Span>,
 }
 
 impl Parsable for Expression {
-    fn parse(&mut self, tokens: &Vec<String>, operators: &Vec<String>, operator_priorities: &Vec<i32>, iterator: &mut i64, parse_end: i64) -> u8 {
-        let mut expr_objs: Vec<ExprObj> = vec![];
-        while *iterator < parse_end &&
-            !tokens.get(*iterator as usize).unwrap().eq(";") &&
-            !tokens.get(*iterator as usize).unwrap().eq("}") &&
-            !tokens.get(*iterator as usize).unwrap().eq(",") &&
-            !tokens.get(*iterator as usize).unwrap().eq(")") {
-            let mut e = ExprObj {
-                expr_obj_type: ExprObjType::Undefined,
-                in_parentheses: None,
-                in_value: None,
-                in_operator: None,
-            };
-
-            e.parse(tokens, operators, operator_priorities, iterator, parse_end, &mut expr_objs);
-            expr_objs.push(e);
-        }
-
-        fn create_expression_from_exprobjs(mut expression: &mut Expression, operators: &Vec<String>, operator_priorities: &Vec<i32>, expr_objs: &Vec<ExprObj>) {
-            enum EOP {
-                Expression(Expression),
-                Operator(String),
+    /// Parses an expression up to the `;`, `,`, `)` or `}` ending it, or up to the end of the tokens.
+    fn parse(&mut self, tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<(), Vec<ParseError>> {
+        *self = parse_expression(tokens, operators, iterator, parse_end, i32::MIN, None)?;
+
+        if *iterator < parse_end {
+            let token = &tokens[*iterator as usize];
+            if is_expression_end(token) {
+                return Ok(());
             }
-
-            let mut max_operator_priority = 0;
-            let mut min_operator_priority = 0;
-            for p in operator_priorities {
-                if *p > max_operator_priority {
-                    max_operator_priority = *p;
-                }
-                if *p < min_operator_priority {
-                    min_operator_priority = *p;
-                }
+            if operators.is_punctuation(token.as_str()) {
+                return Err(vec![Diagnostic::error(format!("Unexpected '{}'.", token).as_str()).at(Some(&token.span))]);
             }
 
-            if expr_objs.is_empty() {
-                println!("Error: Empty expression.");
-                panic!();
-            }
-
-            if expr_objs.len() == 1 {
-                let expr_obj = expr_objs.get(0).unwrap();
-                match expr_obj.expr_obj_type {
-                    ExprObjType::Operator => {
-                        println!("Error: Found operator instead of value.");
-                        panic!();
-                    }
-                    ExprObjType::Parentheses => {
-                        create_expression_from_exprobjs(expression, operators, operator_priorities, expr_obj.in_parentheses.as_ref().unwrap());
-                    }
-                    ExprObjType::Value => {
-                        expression.expression_type = ExpressionType::Value;
-                        expression.value = expr_obj.in_value.clone();
-                    }
-                    ExprObjType::Undefined => {
-                        panic!();
-                    }
-                }
-                return;
-            }
-
-            let mut eops: Vec<EOP> = vec![];
-
-            for eo in expr_objs.iter() {
-                match eo.expr_obj_type {
-                    ExprObjType::Operator => {
-                        eops.push(EOP::Operator(eo.in_operator.as_ref().unwrap().clone().to_string()));
-                    }
-                    ExprObjType::Parentheses => {
-                        let mut new_exp = Expression {
-                            expression_type: ExpressionType::Undefined,
-                            value: None,
-                            left: None,
-                            right: None,
-                            operator: None,
-                        };
-                        create_expression_from_exprobjs(new_exp.borrow_mut(), operators, operator_priorities, eo.in_parentheses.as_ref().unwrap());
-
-                        eops.push(EOP::Expression(new_exp));
-                    }
-                    ExprObjType::Value => {
-                        eops.push(EOP::Expression(Expression {
-                            expression_type: ExpressionType::Value,
-                            value: eo.in_value.clone(),
-                            left: None,
-                            right: None,
-                            operator: None,
-                        }));
-                    }
-                    ExprObjType::Undefined => {
-                        panic!();
-                    }
-                }
-            }
-
-            let mut current_operator_priority = max_operator_priority;
-
-            while current_operator_priority > min_operator_priority - 1 {
-                let mut eops_len = eops.len();
-                let mut i = 0;
-                while i < eops_len {
-                    match eops[i].borrow().clone() {
-                        EOP::Operator(operator_string) => {
-                            if i == 0 {
-                                println!("Error: Found operator instead of value.");
-                                panic!();
-                            }
-                            if i == eops.len() - 1 {
-                                println!("Error: Expression cannot end with an operator.");
-                                panic!();
-                            }
-
-                            if get_operator_priority(operators, operator_priorities, operator_string) == current_operator_priority {
-                                let mut left_expression: Option<Box<Expression>> = None;
-                                let mut right_expression: Option<Box<Expression>> = None;
-
-                                if let EOP::Expression(expression) = eops.get(i - 1).unwrap() {
-                                    left_expression = Some(Box::from(expression.clone()));
-                                }
-                                if let EOP::Expression(expression) = eops.get(i + 1).unwrap() {
-                                    right_expression = Some(Box::from(expression.clone()));
-                                }
-
-                                let operation_expression = Expression {
-                                    expression_type: ExpressionType::Operation,
-                                    value: None,
-                                    left: left_expression,
-                                    right: right_expression,
-                                    operator: Some(operator_string.clone()),
-                                };
-
-                                eops[i] = EOP::Expression(operation_expression);
-                                eops.remove(i + 1);
-                                eops.remove(i - 1);
-
-                                eops_len -= 2;
-                                i -= 1;
-                            }
-                        }
-                        EOP::Expression(_) => {}
-                    }
-                    i += 1;
-                }
-
-                current_operator_priority -= 1;
+            let mut diagnostic = Diagnostic::error("Expected an operator between two values.").at(Some(&token.span));
+            if tokens[*iterator as usize - 1].span.line != token.span.line {
+                diagnostic = diagnostic.with_help("a ';' may be missing at the end of the previous line");
             }
+            return Err(vec![diagnostic]);
+        }
+        return Ok(());
+    }
+}
 
-            if let EOP::Expression(result) = eops.pop().unwrap() {
-                *expression = result;
-            }
+impl Drop for Expression {
+    /// Long chains of operators make trees as deep as they are long, `1 + 1 + ... + 1` nesting each addition in the
+    /// next, so the operands are dropped from a list rather than by nested calls.
+    fn drop(&mut self) {
+        let mut operands: Vec<Box<Expression>> = vec![];
+        operands.extend(self.left.take());
+        operands.extend(self.right.take());
+        while let Some(mut operand) = operands.pop() {
+            operands.extend(operand.left.take());
+            operands.extend(operand.right.take());
         }
-        create_expression_from_exprobjs(self, operators, operator_priorities, expr_objs.borrow());
-        return 0;
     }
 }
 
 impl Clone for Expression {
     fn clone(&self) -> Expression {
         Expression {
-            expression_type: self.expression_type.clone(),
+            expression_type: self.expression_type,
             left: self.left.clone(),
             right: self.right.clone(),
             value: self.value.clone(),
             operator: self.operator.clone(),
+            span: self.span.clone(),
         }
     }
 }
@@ -228,15 +120,32 @@ impl Dumpable for Expression {
                 return self.value.as_ref().unwrap().get_dump();
             }
             ExpressionType::Operation => {
+                // Prefix operators have no left operand and postfix ones no right operand, except calls.
                 let mut str = "(".to_string();
-                str.push_str(self.left.as_ref().unwrap().get_dump().as_str());
-                str.push_str(" [");
-                str.push_str(self.operator.as_ref().unwrap().as_str());
-                str.push_str("] ");
-                str.push_str(self.right.as_ref().unwrap().get_dump().as_str());
+                if let Some(left) = &self.left {
+                    str.push_str(left.get_dump().as_str());
+                    str.push_str(" ");
+                }
+                str.push_str("[");
+                str.push_str(self.operator.as_ref().unwrap().symbol.as_str());
+                str.push_str("]");
+                if let Some(right) = &self.right {
+                    str.push_str(" ");
+                    str.push_str(right.get_dump().as_str());
+                }
                 str.push_str(")");
                 return str;
             }
+            ExpressionType::Array => {
+                let mut str = "array ".to_string();
+                str.push_str(self.value.as_ref().unwrap().constant.as_ref().unwrap().get_dump().as_str());
+                return str;
+            }
+            ExpressionType::Object => {
+                let mut str = "object ".to_string();
+                str.push_str(self.value.as_ref().unwrap().constant.as_ref().unwrap().get_dump().as_str());
+                return str;
+            }
             ExpressionType::Undefined => {
                 return "Undefined".to_string();
             }
@@ -250,29 +159,71 @@ impl Dumpable for Expression {
 
 pub enum Constant{
     Undefined,
+    Null,
     Integer(i64),
-    Function(Rc<RefCell<Callable>>),
-    Tuple(Rc<RefCell<Tuple>>)
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Function(Rc<RefCell<dyn Callable>>),
+    Tuple(Rc<RefCell<Tuple>>),
+    /// A value made of named members only.
+    Object(ObjectMembers),
+    Array(ArrayElements)
 }
 
+/// The members of an object, shared by every variable holding it.
+pub type ObjectMembers = Rc<RefCell<Vec<Rc<RefCell<Variable>>>>>;
+
+/// The elements of an array, shared by every variable holding it.
+pub type ArrayElements = Rc<RefCell<Vec<Rc<RefCell<Variable>>>>>;
+
 impl Clone for Constant {
     fn clone(&self) -> Self {
         return match self {
             Constant::Undefined => Constant::Undefined,
+            Constant::Null => Constant::Null,
             Constant::Integer(i) => Constant::Integer(*i),
+            Constant::Float(f) => Constant::Float(*f),
+            Constant::Boolean(b) => Constant::Boolean(*b),
+            Constant::String(s) => Constant::String(s.clone()),
             Constant::Function(f) => Constant::Function(f.clone()),
-            Constant::Tuple(t) => Constant::Tuple(t.clone())
+            Constant::Tuple(t) => Constant::Tuple(t.clone()),
+            Constant::Object(o) => Constant::Object(o.clone()),
+            Constant::Array(a) => Constant::Array(a.clone())
         }
     }
 }
 
+impl Constant {
+    pub fn get_type_name(&self) -> &'static str {
+        return match self {
+            Constant::Undefined => "undefined",
+            Constant::Null => "null",
+            Constant::Integer(_) => "integer",
+            Constant::Float(_) => "float",
+            Constant::Boolean(_) => "boolean",
+            Constant::String(_) => "string",
+            Constant::Function(_) => "function",
+            Constant::Tuple(_) => "tuple",
+            Constant::Object(_) => "object",
+            Constant::Array(_) => "array"
+        };
+    }
+}
+
 impl Dumpable for Constant {
     fn get_dump(&self) -> String {
         return match self {
             Constant::Undefined => "Undefined".to_string(),
+            Constant::Null => "null".to_string(),
             Constant::Integer(i) => i.to_string(),
+            Constant::Float(f) => format!("{:?}", f),
+            Constant::Boolean(b) => b.to_string(),
+            Constant::String(s) => s.clone(),
             Constant::Function(f) => f.deref().borrow().get_dump(),
-            Constant::Tuple(t) => t.deref().borrow().get_dump()
+            Constant::Tuple(t) => t.deref().borrow().get_dump(),
+            Constant::Object(_) => "object".to_string(),
+            Constant::Array(a) => get_array_dump(a, &mut vec![])
         };
     }
 
@@ -335,262 +286,297 @@ impl Dumpable for Value {
     }
 }
 
-enum ExprObjType {
+pub enum StatementKind {
     Undefined,
-    Parentheses,
-    Operator,
-    Value,
+    Expression(Expression),
+    VariableDeclaration(Expression),
+    ReturnStatement(Expression),
+    OperatorDeclaration(String, Expression),
+    /// The `if` and `else if` branches in order, then the `else` block.
+    IfStatement(Vec<(Expression, Rc<Scope>)>, Option<Rc<Scope>>),
+    WhileStatement(Expression, Rc<Scope>),
+    /// `for (initialization; condition; update) { ... }`, each part of the header being optional.
+    ForStatement(Option<Box<Statement>>, Option<Expression>, Option<Expression>, Rc<Scope>),
+    /// `for (let name of collection) { ... }`.
+    ForOfStatement(String, Expression, Rc<Scope>),
+    BreakStatement,
+    ContinueStatement,
+    ThrowStatement(Expression),
+    /// `delete object.name` or `delete object["name"]`.
+    DeleteStatement(Expression),
+    /// `try { ... } catch (name) { ... } finally { ... }`, the catch binding, the catch block or the finally block
+    /// being optional.
+    TryStatement(Rc<Scope>, Option<(Option<String>, Rc<Scope>)>, Option<Rc<Scope>>)
 }
 
-struct ExprObj {
-    expr_obj_type: ExprObjType,
-    in_parentheses: Option<Vec<ExprObj>>,
-    in_operator: Option<String>,
-    in_value: Option<Value>,
-}
-
-impl ExprObj {
-    fn parse(&mut self, tokens: &Vec<String>, operators: &Vec<String>, operator_priorities: &Vec<i32>, iterator: &mut i64, parse_end: i64, all: &mut Vec<ExprObj>) -> u8 {
-        let mut token: &String = tokens.get(*iterator as usize).unwrap();
-
-        if token.eq("function") {
-            *iterator += 2;
-            let mut args: Vec<String> = vec![];
-            let mut expected_variable_name = true;
-            while !token.to_string().eq(")") {
-                if expected_variable_name {
-                    let mut e = ExprObj {
-                        expr_obj_type: ExprObjType::Undefined,
-                        in_parentheses: None,
-                        in_value: None,
-                        in_operator: None,
-                    };
-                    e.parse(tokens, operators, operator_priorities, iterator, parse_end, all);
-                    match e.expr_obj_type {
-                        ExprObjType::Value => {
-                            match e.in_value.as_ref().unwrap().value_type {
-                                ValueType::VariableName => {
-                                    args.push(e.in_value.unwrap().variable.unwrap());
-                                },
-                                _ => {
-                                    println!("Error: expected variable name.");
-                                    panic!();
-                                }
-                            }
-                        },
-                        _ => {
-                            println!("Error: expected variable name.");
-                            panic!();
-                        }
-                    }
-                }
-                else {
-                    if !token.eq(",") {
-                        println!("Error: Expected operator ','.");
-                        panic!();
-                    }
-                    *iterator += 1;
-                }
-                expected_variable_name = !expected_variable_name;
-
-                token = tokens.get(*iterator as usize).unwrap();
-            }
-
-            *iterator += 2;
-
-            self.expr_obj_type = ExprObjType::Value;
-            self.in_value = Some(Value{
-                value_type: ValueType::Constant,
-                variable: None,
-                constant: Some(Constant::Function(Rc::new(RefCell::new(Function{
-                    scope: Rc::new(RefCell::new(Scope::parse(tokens, operators, operator_priorities, iterator, parse_end))),
-                    args
-                }))))
-            });
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Option<Span>,
+}
 
-            *iterator += 1;
-            return 0;
-        }
+impl Statement {
+    pub fn parse(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<Statement, Vec<ParseError>> {
+        let start = *iterator;
+        let kind = Statement::parse_kind(tokens, operators, iterator, parse_end)?;
+        return Ok(Statement {
+            kind,
+            span: get_tokens_span(tokens, start, *iterator)
+        });
+    }
 
-        let previous = all.last();
-        if token.eq("(") {
-            if previous.is_some() {
-                if let ExprObjType::Operator = previous.unwrap().expr_obj_type {}
-                else {
-                    // Function call
+    fn parse_kind(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<StatementKind, Vec<ParseError>> {
+        let token = get_token(tokens, *iterator, parse_end)?;
+        // Strings never start a statement, even when their text is a keyword.
+        let keyword = if token.is(token.as_str()) { token.as_str() } else { "" };
+        match keyword {
+            "let" => {
+                *iterator += 1;
+                let mut expression = Expression{
+                    expression_type: ExpressionType::Undefined,
+                    left: None,
+                    right: None,
+                    value: None,
+                    operator: None,
+                    span: None
+                };
+                expression.parse(tokens, operators, iterator, parse_end)?;
+                return Ok(VariableDeclaration(expression));
+            },
+            "return" => {
+                *iterator += 1;
+                let mut expression = Expression{
+                    expression_type: ExpressionType::Undefined,
+                    left: None,
+                    right: None,
+                    value: None,
+                    operator: None,
+                    span: None
+                };
+                expression.parse(tokens, operators, iterator, parse_end)?;
+                return Ok(ReturnStatement(expression));
+            }
+            "if" => {
+                let mut branches: Vec<(Expression, Rc<Scope>)> = vec![];
+                let mut else_scope: Option<Rc<Scope>> = None;
+                loop {
                     *iterator += 1;
-                    token = tokens.get(*iterator as usize).unwrap();
-                    let mut exprs: Vec<Expression> = vec![];
-                    let mut expected_expression = true;
-                    while !token.to_string().eq(")") {
-                        if expected_expression {
-                            let mut e = Expression {
-                                expression_type: ExpressionType::Undefined,
-                                value: None,
-                                operator: None,
-                                left: None,
-                                right: None
-                            };
-                            e.parse(tokens, operators, operator_priorities, iterator, parse_end);
-                            exprs.push(e);
-                        }
-                        else {
-                            if !token.eq(",") {
-                                println!("Error: Expected operator ','.");
-                                panic!();
-                            }
-                            *iterator += 1;
-                        }
-                        expected_expression = !expected_expression;
+                    let condition = parse_condition(tokens, operators, iterator, parse_end)?;
+                    let scope = parse_block(tokens, operators, iterator, parse_end)?;
+                    branches.push((condition, Rc::new(scope)));
 
-                        token = tokens.get(*iterator as usize).unwrap();
+                    if !is_token(tokens, *iterator, parse_end, "else") {
+                        break;
                     }
-
-                    self.expr_obj_type = ExprObjType::Value;
-                    self.in_value = Some(Value {
-                        value_type: ValueType::Constant,
-                        variable: None,
-                        constant: Some(Constant::Tuple(Rc::new(RefCell::new(Tuple {
-                            expressions: exprs
-                        }))))
-                    });
-
-                    all.push(ExprObj {
-                        expr_obj_type: ExprObjType::Operator,
-                        in_value: None,
-                        in_parentheses: None,
-                        in_operator: Some("(".to_string())
-                    });
-
                     *iterator += 1;
-                    return 0;
+                    if is_token(tokens, *iterator, parse_end, "if") {
+                        continue;
+                    }
+                    else_scope = Some(Rc::new(parse_block(tokens, operators, iterator, parse_end)?));
+                    break;
                 }
+                return Ok(StatementKind::IfStatement(branches, else_scope));
             }
-
-            // Mathematical parentheses
-            *iterator += 1;
-            token = tokens.get(*iterator as usize).unwrap();
-            let mut expr_objs: Vec<ExprObj> = vec![];
-            while !token.to_string().eq(")") {
-                let mut e = ExprObj {
-                    expr_obj_type: ExprObjType::Undefined,
-                    in_parentheses: None,
-                    in_value: None,
-                    in_operator: None,
-                };
-
-                e.parse(tokens, operators, operator_priorities, iterator, parse_end, all);
-                expr_objs.push(e);
-                token = tokens.get(*iterator as usize).unwrap();
-            }
-            self.expr_obj_type = ExprObjType::Parentheses;
-            self.in_parentheses = Some(expr_objs);
-
-            *iterator += 1;
-            return 0;
-        }
-
-        if operator_exists(operators, token) {
-            self.expr_obj_type = ExprObjType::Operator;
-            self.in_operator = Some(token.to_string());
-
-            *iterator += 1;
-            return 0;
-        } else {
-            let test = token.parse::<i64>();
-            let is_integer: bool = test.is_ok();
-            if is_integer {
-                self.expr_obj_type = ExprObjType::Value;
-                self.in_value = Some(Value {
-                    value_type: ValueType::Constant,
-                    variable: None,
-                    constant: Some(Constant::Integer(test.ok().unwrap())
-                    ),
-                });
-
+            "while" => {
                 *iterator += 1;
-                return 0;
+                let condition = parse_condition(tokens, operators, iterator, parse_end)?;
+                let scope = parse_block(tokens, operators, iterator, parse_end)?;
+                return Ok(StatementKind::WhileStatement(condition, Rc::new(scope)));
             }
+            "for" => {
+                *iterator += 1;
+                if !is_token(tokens, *iterator, parse_end, "(") {
+                    return Err(vec![Diagnostic::error("Expected '('.").at(get_token_span(tokens, *iterator))]);
+                }
+                *iterator += 1;
 
-            self.expr_obj_type = ExprObjType::Value;
-            self.in_value = Some(Value{
-                value_type: ValueType::VariableName,
-                variable: Some(token.to_string()),
-                constant: None
-            });
+                if *iterator + 2 < parse_end && is_token(tokens, *iterator, parse_end, "let")
+                    && tokens.get(*iterator as usize + 2).unwrap().eq("of") {
+                    let token = tokens.get(*iterator as usize + 1).unwrap();
+                    if !matches!(token.kind, TokenKind::Identifier) {
+                        return Err(vec![Diagnostic::error("Expected variable name.").at(Some(&token.span))]);
+                    }
+                    let name = token.text.clone();
+                    *iterator += 3;
+
+                    let mut collection = Expression {
+                        expression_type: ExpressionType::Undefined,
+                        left: None,
+                        right: None,
+                        value: None,
+                        operator: None,
+                        span: None
+                    };
+                    collection.parse(tokens, operators, iterator, parse_end)?;
+                    expect_token(tokens, ")", iterator, parse_end)?;
 
-            *iterator += 1;
-            return 0;
-        }
-    }
-}
+                    let scope = parse_block(tokens, operators, iterator, parse_end)?;
+                    return Ok(StatementKind::ForOfStatement(name, collection, Rc::new(scope)));
+                }
 
-impl Dumpable for ExprObj {
-    fn get_dump(&self) -> String {
-        match self.expr_obj_type {
-            ExprObjType::Undefined => {
-                return "Undefined".to_string();
-            }
-            ExprObjType::Parentheses => {
-                let mut str = "(\n".to_string();
-                for e in self.in_parentheses.as_ref().unwrap().iter() {
-                    str.push_str(e.get_dump().as_str());
-                    str.push_str("\n");
+                let mut initialization: Option<Box<Statement>> = None;
+                if !is_token(tokens, *iterator, parse_end, ";") {
+                    initialization = Some(Box::new(Statement::parse(tokens, operators, iterator, parse_end)?));
                 }
-                str.push_str(")");
-                return str;
+                expect_token(tokens, ";", iterator, parse_end)?;
+
+                let mut condition: Option<Expression> = None;
+                if !is_token(tokens, *iterator, parse_end, ";") {
+                    let mut expression = Expression {
+                        expression_type: ExpressionType::Undefined,
+                        left: None,
+                        right: None,
+                        value: None,
+                        operator: None,
+                        span: None
+                    };
+                    expression.parse(tokens, operators, iterator, parse_end)?;
+                    condition = Some(expression);
+                }
+                expect_token(tokens, ";", iterator, parse_end)?;
+
+                let mut update: Option<Expression> = None;
+                if !is_token(tokens, *iterator, parse_end, ")") {
+                    let mut expression = Expression {
+                        expression_type: ExpressionType::Undefined,
+                        left: None,
+                        right: None,
+                        value: None,
+                        operator: None,
+                        span: None
+                    };
+                    expression.parse(tokens, operators, iterator, parse_end)?;
+                    update = Some(expression);
+                }
+                expect_token(tokens, ")", iterator, parse_end)?;
+
+                let scope = parse_block(tokens, operators, iterator, parse_end)?;
+                return Ok(StatementKind::ForStatement(initialization, condition, update, Rc::new(scope)));
             }
-            ExprObjType::Operator => {
-                let mut str = "Operator ".to_string();
-                str.push_str(self.in_operator.as_ref().unwrap().as_str());
-                return str;
+            "break" => {
+                *iterator += 1;
+                return Ok(StatementKind::BreakStatement);
             }
-            ExprObjType::Value => {
-                let mut str = "Value ".to_string();
-                str.push_str(self.in_value.as_ref().unwrap().get_dump().as_str());
-                return str;
+            "continue" => {
+                *iterator += 1;
+                return Ok(StatementKind::ContinueStatement);
             }
-        }
-    }
-
-    fn dump(&self) {
-        println!("{}", self.get_dump());
-    }
-}
-
-pub enum Statement {
-    Undefined,
-    Expression(Expression),
-    VariableDeclaration(Expression),
-    ReturnStatement(Expression)
-}
-
-impl Statement {
-    pub fn parse(tokens: &Vec<String>, operators: &Vec<String>, operator_priorities: &Vec<i32>, iterator: &mut i64, parse_end: i64) -> Statement {
-        match tokens.get(*iterator as usize).unwrap().as_str() {
-            "let" => {
+            "throw" => {
                 *iterator += 1;
-                let mut expression = Expression{
+                let mut expression = Expression {
                     expression_type: ExpressionType::Undefined,
                     left: None,
                     right: None,
                     value: None,
-                    operator: None
+                    operator: None,
+                    span: None
                 };
-                expression.parse(tokens, operators, operator_priorities, iterator, parse_end);
-                return VariableDeclaration(expression);
-            },
-            "return" => {
+                expression.parse(tokens, operators, iterator, parse_end)?;
+                return Ok(StatementKind::ThrowStatement(expression));
+            }
+            "delete" => {
                 *iterator += 1;
-                let mut expression = Expression{
+                let mut expression = Expression {
                     expression_type: ExpressionType::Undefined,
                     left: None,
                     right: None,
                     value: None,
-                    operator: None
+                    operator: None,
+                    span: None
+                };
+                expression.parse(tokens, operators, iterator, parse_end)?;
+                let is_member = match &expression.operator {
+                    Some(operator) => matches!(operator.hook, OperatorHook::Member | OperatorHook::Index),
+                    None => false
                 };
-                expression.parse(tokens, operators, operator_priorities, iterator, parse_end);
-                return ReturnStatement(expression);
+                if !is_member {
+                    return Err(vec![Diagnostic::error("Only members can be deleted.").at(expression.span.as_ref())]);
+                }
+                return Ok(StatementKind::DeleteStatement(expression));
+            }
+            "try" => {
+                let start = *iterator;
+                *iterator += 1;
+                let scope = parse_block(tokens, operators, iterator, parse_end)?;
+
+                let mut catch: Option<(Option<String>, Rc<Scope>)> = None;
+                if is_token(tokens, *iterator, parse_end, "catch") {
+                    *iterator += 1;
+                    let name = parse_catch_binding(tokens, iterator, parse_end)?;
+                    catch = Some((name, Rc::new(parse_block(tokens, operators, iterator, parse_end)?)));
+                }
+
+                let mut finally_scope: Option<Rc<Scope>> = None;
+                if is_token(tokens, *iterator, parse_end, "finally") {
+                    *iterator += 1;
+                    finally_scope = Some(Rc::new(parse_block(tokens, operators, iterator, parse_end)?));
+                }
+
+                if catch.is_none() && finally_scope.is_none() {
+                    return Err(vec![Diagnostic::error("Expected 'catch' or 'finally'.").at(get_token_span(tokens, *iterator))
+                        .with_label(get_token_span(tokens, start), "to complete this")
+                        ]);
+                }
+                return Ok(StatementKind::TryStatement(Rc::new(scope), catch, finally_scope));
+            }
+            "operator" => {
+                *iterator += 1;
+                let symbol = get_token(tokens, *iterator, parse_end)?.to_string();
+                let is_custom = match operators.get(symbol.as_str()) {
+                    Some(operator) => matches!(operator.hook, OperatorHook::Custom),
+                    None => false
+                };
+                if !is_custom {
+                    // The declaration wasn't added to the table, so the symbol may still be split into several tokens.
+                    let (declared_symbol, arguments_start) = get_declared_symbol(tokens, *iterator as usize);
+                    if operators.get(declared_symbol.as_str()).is_some() || operators.is_punctuation(declared_symbol.as_str()) {
+                        return Err(vec![Diagnostic::error(format!("Cannot redefine built-in operator '{}'.", declared_symbol).as_str()).at(get_tokens_span(tokens, *iterator, arguments_start as i64).as_ref())
+                            .with_help("objects can overload built-in operators with members like '__add__'")
+                            ]);
+                    }
+                    if is_valid_custom_symbol(declared_symbol.as_str()) {
+                        // A valid new symbol is only left out of the table when its priority is missing.
+                        let mut arguments_end = arguments_start;
+                        while arguments_end < tokens.len() && !tokens[arguments_end].eq(")") {
+                            arguments_end += 1;
+                        }
+                        return Err(vec![get_missing_priority_error(tokens, arguments_end as i64 + 1, declared_symbol.as_str())]);
+                    }
+                    return Err(vec![Diagnostic::error("Invalid operator declaration, expected a symbol followed by '(a, b) priority <n>'.").at(get_token_span(tokens, *iterator))]);
+                }
+
+                *iterator += 1;
+                let arguments_start = *iterator;
+                let args = parse_function_arguments(tokens, operators, iterator, parse_end)?;
+                if args.len() != 2 {
+                    return Err(vec![Diagnostic::error(format!("Operator '{}' must take exactly two arguments.", symbol).as_str()).at(get_tokens_span(tokens, arguments_start, *iterator).as_ref())]);
+                }
+
+                // The priority was already read when the operator was added to the table.
+                if get_declared_priority(tokens, *iterator as usize).is_none() {
+                    return Err(vec![get_missing_priority_error(tokens, *iterator, symbol.as_str())]);
+                }
+                *iterator += 2;
+                if tokens.get(*iterator as usize - 1).unwrap().eq("-") {
+                    *iterator += 1;
+                }
+
+                let function = Expression {
+                    expression_type: ExpressionType::Value,
+                    left: None,
+                    right: None,
+                    value: Some(Value {
+                        value_type: ValueType::Constant,
+                        variable: None,
+                        constant: Some(Constant::Function(Rc::new(RefCell::new(Function {
+                            scope: Rc::new(parse_block(tokens, operators, iterator, parse_end)?),
+                            args,
+                            closure: None
+                        }))))
+                    }),
+                    operator: None,
+                    span: None
+                };
+                return Ok(StatementKind::OperatorDeclaration(symbol, function));
             }
             _ => {
                 let mut expression = Expression{
@@ -598,57 +584,206 @@ impl Statement {
                     left: None,
                     right: None,
                     value: None,
-                    operator: None
+                    operator: None,
+                    span: None
                 };
-                expression.parse(tokens, operators, operator_priorities, iterator, parse_end);
-                return Statement::Expression(expression);
+                expression.parse(tokens, operators, iterator, parse_end)?;
+                return Ok(StatementKind::Expression(expression));
             }
         };
     }
 }
 
-impl Clone for Statement {
+impl Clone for StatementKind {
     fn clone(&self) -> Self {
         match self {
-            Statement::Undefined => {
-                return Statement::Undefined;
+            StatementKind::Undefined => {
+                return StatementKind::Undefined;
+            },
+            StatementKind::Expression(e) => {
+                return StatementKind::Expression(e.clone());
+            },
+            StatementKind::VariableDeclaration(e) => {
+                return StatementKind::VariableDeclaration(e.clone());
+            },
+            StatementKind::ReturnStatement(e) => {
+                return StatementKind::ReturnStatement(e.clone());
+            },
+            StatementKind::OperatorDeclaration(symbol, e) => {
+                return StatementKind::OperatorDeclaration(symbol.clone(), e.clone());
+            },
+            StatementKind::IfStatement(branches, else_scope) => {
+                return StatementKind::IfStatement(branches.clone(), else_scope.clone());
+            },
+            StatementKind::WhileStatement(condition, scope) => {
+                return StatementKind::WhileStatement(condition.clone(), scope.clone());
+            },
+            StatementKind::ForStatement(initialization, condition, update, scope) => {
+                return StatementKind::ForStatement(initialization.clone(), condition.clone(), update.clone(), scope.clone());
             },
-            Statement::Expression(e) => {
-                return Statement::Expression(e.clone());
+            StatementKind::ForOfStatement(name, collection, scope) => {
+                return StatementKind::ForOfStatement(name.clone(), collection.clone(), scope.clone());
             },
-            Statement::VariableDeclaration(e) => {
-                return Statement::VariableDeclaration(e.clone());
+            StatementKind::BreakStatement => {
+                return StatementKind::BreakStatement;
             },
-            Statement::ReturnStatement(e) => {
-                return Statement::ReturnStatement(e.clone());
+            StatementKind::ContinueStatement => {
+                return StatementKind::ContinueStatement;
+            },
+            StatementKind::ThrowStatement(e) => {
+                return StatementKind::ThrowStatement(e.clone());
+            },
+            StatementKind::DeleteStatement(e) => {
+                return StatementKind::DeleteStatement(e.clone());
+            },
+            StatementKind::TryStatement(scope, catch, finally_scope) => {
+                return StatementKind::TryStatement(scope.clone(), catch.clone(), finally_scope.clone());
             }
         };
     }
 }
 
+impl Clone for Statement {
+    fn clone(&self) -> Self {
+        return Statement {
+            kind: self.kind.clone(),
+            span: self.span.clone()
+        };
+    }
+}
+
 impl Dumpable for Statement {
+    fn get_dump(&self) -> String {
+        return self.kind.get_dump();
+    }
+
+    fn dump(&self) {
+        println!("{}", self.get_dump());
+    }
+}
+
+impl Dumpable for StatementKind {
     fn get_dump(&self) -> String {
         match self {
-            Statement::Undefined => {
+            StatementKind::Undefined => {
                 return "[Undefined]".to_string();
             },
-            Statement::Expression(expression) => {
+            StatementKind::Expression(expression) => {
                 let mut result = "[expression : ".to_string();
                 result += expression.get_dump().as_str();
                 result += "]";
                 return result;
             },
-            Statement::VariableDeclaration(expression) => {
+            StatementKind::VariableDeclaration(expression) => {
                 let mut result = "[let : ".to_string();
                 result += expression.get_dump().as_str();
                 result += "]";
                 return result;
             },
-            Statement::ReturnStatement(expression) => {
+            StatementKind::ReturnStatement(expression) => {
                 let mut result = "[return : ".to_string();
                 result += expression.get_dump().as_str();
                 result += "]";
                 return result;
+            },
+            StatementKind::OperatorDeclaration(symbol, expression) => {
+                let mut result = "[operator ".to_string();
+                result += symbol.as_str();
+                result += " : ";
+                result += expression.get_dump().as_str();
+                result += "]";
+                return result;
+            },
+            StatementKind::IfStatement(branches, else_scope) => {
+                let mut result = "".to_string();
+                for (condition, scope) in branches.iter() {
+                    if result.is_empty() {
+                        result += "[if : ";
+                    } else {
+                        result += " else if : ";
+                    }
+                    result += condition.get_dump().as_str();
+                    result += " ";
+                    result += scope.get_dump().as_str();
+                }
+                if else_scope.is_some() {
+                    result += " else ";
+                    result += else_scope.as_ref().unwrap().get_dump().as_str();
+                }
+                result += "]";
+                return result;
+            },
+            StatementKind::WhileStatement(condition, scope) => {
+                let mut result = "[while : ".to_string();
+                result += condition.get_dump().as_str();
+                result += " ";
+                result += scope.get_dump().as_str();
+                result += "]";
+                return result;
+            },
+            StatementKind::ForStatement(initialization, condition, update, scope) => {
+                let mut result = "[for : ".to_string();
+                if initialization.is_some() {
+                    result += initialization.as_ref().unwrap().get_dump().as_str();
+                }
+                result += " ; ";
+                if condition.is_some() {
+                    result += condition.as_ref().unwrap().get_dump().as_str();
+                }
+                result += " ; ";
+                if update.is_some() {
+                    result += update.as_ref().unwrap().get_dump().as_str();
+                }
+                result += " ";
+                result += scope.get_dump().as_str();
+                result += "]";
+                return result;
+            },
+            StatementKind::ForOfStatement(name, collection, scope) => {
+                let mut result = "[for : let ".to_string();
+                result += name.as_str();
+                result += " of ";
+                result += collection.get_dump().as_str();
+                result += " ";
+                result += scope.get_dump().as_str();
+                result += "]";
+                return result;
+            },
+            StatementKind::BreakStatement => {
+                return "[break]".to_string();
+            },
+            StatementKind::ContinueStatement => {
+                return "[continue]".to_string();
+            },
+            StatementKind::ThrowStatement(expression) => {
+                let mut result = "[throw : ".to_string();
+                result += expression.get_dump().as_str();
+                result += "]";
+                return result;
+            },
+            StatementKind::DeleteStatement(expression) => {
+                let mut result = "[delete : ".to_string();
+                result += expression.get_dump().as_str();
+                result += "]";
+                return result;
+            },
+            StatementKind::TryStatement(scope, catch, finally_scope) => {
+                let mut result = "[try : ".to_string();
+                result += scope.get_dump().as_str();
+                if let Some((name, catch_scope)) = catch {
+                    result += " catch ";
+                    if let Some(name) = name {
+                        result += name.as_str();
+                        result += " ";
+                    }
+                    result += catch_scope.get_dump().as_str();
+                }
+                if let Some(finally_scope) = finally_scope {
+                    result += " finally ";
+                    result += finally_scope.get_dump().as_str();
+                }
+                result += "]";
+                return result;
             }
         }
     }
@@ -658,37 +793,92 @@ impl Dumpable for Statement {
     }
 }
 
+/// A block of parsed statements. It never changes once parsed, the variables of a running block living in an
+/// `Environment`.
 pub struct Scope {
     pub statements: Vec<Statement>,
-    pub parent: Option<Rc<RefCell<Scope>>>,
-    pub accessible_variables: Vec<Rc<RefCell<Variable>>>,
-    pub return_value: Option<Rc<RefCell<Variable>>>
+    pub span: Option<Span>
 }
 
 impl Scope {
-    pub fn parse(tokens: &Vec<String>, operators: &Vec<String>, operator_priorities: &Vec<i32>, iterator: &mut i64, parse_end: i64) -> Scope {
+    /// Parses statements until the end of the tokens or a `}` closing the block.
+    ///
+    /// A statement that fails to parse is skipped, so the errors of all the statements are reported together.
+    pub fn parse(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<Scope, Vec<ParseError>> {
+        let start = *iterator;
         let mut result_statements: Vec<Statement> = vec![];
-        while *iterator < parse_end{
-            if tokens.get(*iterator as usize).unwrap().eq("}") {
+        let mut errors: Vec<ParseError> = vec![];
+        while *iterator < parse_end {
+            if is_token(tokens, *iterator, parse_end, "}") {
                 break;
             }
-            result_statements.push(Statement::parse(tokens, operators, operator_priorities, iterator, parse_end));
-            *iterator += 1;
+            if is_token(tokens, *iterator, parse_end, ";") {
+                *iterator += 1;
+                continue;
+            }
+
+            let statement_start = *iterator;
+            match Statement::parse(tokens, operators, iterator, parse_end) {
+                Ok(statement) => result_statements.push(statement),
+                Err(mut statement_errors) => {
+                    errors.append(&mut statement_errors);
+                    if is_nesting_error(&errors) {
+                        return Err(errors);
+                    }
+                    skip_failed_statement(tokens, iterator, parse_end, statement_start);
+                    errors.append(&mut parse_remaining_blocks(tokens, operators, iterator, parse_end));
+                    continue;
+                }
+            }
+
+            // Statements ending with a block don't need a ';'.
+            if *iterator < parse_end {
+                let token = &tokens[*iterator as usize];
+                if token.eq(";") {
+                    *iterator += 1;
+                } else if token.eq(",") || token.eq(")") {
+                    errors.push(Diagnostic::error(format!("Unexpected '{}'.", token).as_str()).at(Some(&token.span)));
+                    skip_statement(tokens, iterator, parse_end, 0);
+                }
+            }
         }
-        return Scope {
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        return Ok(Scope {
             statements: result_statements,
-            parent: Some(Rc::new(RefCell::new(Scope {
-                statements: vec![],
-                parent: None,
-                accessible_variables: vec![Rc::new(RefCell::new(Variable {
-                    name: Some("variable_in_parent".to_string()),
-                    constant: Some(Constant::Integer(15)),
-                    members: vec![]
-                }))],
-                return_value: None
-            }))),
-            accessible_variables: vec![],
-            return_value: None
+            span: get_tokens_span(tokens, start, *iterator)
+        });
+    }
+
+    /// Parses a whole source, a `}` closing no block is an error rather than its end.
+    pub fn parse_program(tokens: &Vec<Token>, operators: &OperatorTable) -> Result<Scope, Vec<ParseError>> {
+        let parse_end = tokens.len() as i64;
+        let mut iterator = 0;
+        let result = Scope::parse(tokens, operators, &mut iterator, parse_end);
+        if let Err(errors) = &result {
+            if is_nesting_error(errors) {
+                return result;
+            }
+        }
+
+        let mut errors: Vec<ParseError> = vec![];
+        while iterator < parse_end {
+            errors.push(Diagnostic::error("Unexpected '}'.").at(get_token_span(tokens, iterator)));
+            iterator += 1;
+            if let Err(mut scope_errors) = Scope::parse(tokens, operators, &mut iterator, parse_end) {
+                errors.append(&mut scope_errors);
+            }
+        }
+
+        return match result {
+            Ok(scope) if errors.is_empty() => Ok(scope),
+            Ok(_) => Err(errors),
+            Err(mut scope_errors) => {
+                scope_errors.append(&mut errors);
+                Err(scope_errors)
+            }
         };
     }
 }
@@ -696,10 +886,8 @@ impl Scope {
 impl Clone for Scope {
     fn clone(&self) -> Self {
         return Scope {
-            accessible_variables: self.accessible_variables.clone(),
             statements: self.statements.clone(),
-            parent: self.parent.clone(),
-            return_value: self.return_value.clone()
+            span: self.span.clone()
         }
     }
 }
@@ -711,11 +899,6 @@ impl Dumpable for Scope {
             str.push_str(s.get_dump().as_str());
             str.push_str("\n");
         }
-        str.push_str("---\n");
-        for v in self.accessible_variables.iter() {
-            str.push_str(v.deref().borrow().get_dump().as_str());
-            str.push_str("\n");
-        }
         str.push_str("}");
 
         return str;
@@ -726,8 +909,11 @@ impl Dumpable for Scope {
     }
 }
 
+/// Syntax errors are reported as diagnostics.
+pub type ParseError = Diagnostic;
+
 pub trait Parsable {
-    fn parse(&mut self, tokens: &Vec<String>, operators: &Vec<String>, operator_priorities: &Vec<i32>, iterator: &mut i64, parse_end: i64) -> u8;
+    fn parse(&mut self, tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<(), Vec<ParseError>>;
 }
 
 pub trait Dumpable {
@@ -735,23 +921,599 @@ pub trait Dumpable {
     fn dump(&self);
 }
 
-fn get_operator_priority(operators: &Vec<String>, operator_priorities: &Vec<i32>, operator: &String) -> i32 {
-    let mut opsi = 0;
-    for op_search in operators.iter() {
-        if op_search.eq(operator) {
-            return *operator_priorities.get(opsi).unwrap();
+/// Parses the operators binding at least as tightly as the given priority, by precedence climbing.
+///
+/// The right operand of a left associative operator only takes the operators binding tighter than it, so `a - b - c`
+/// is `(a - b) - c`, while the one of a right associative operator also takes the operators sharing its priority, so
+/// `a = b = c` is `a = (b = c)`. `after` is the operator the expression follows, if any.
+fn parse_expression(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64, min_priority: i32, after: Option<&Span>) -> Result<Expression, Vec<ParseError>> {
+    check_nesting(tokens, *iterator)?;
+    let start = *iterator;
+    let mut left = parse_operand(tokens, operators, iterator, parse_end, after)?;
+
+    while *iterator < parse_end {
+        let token = &tokens[*iterator as usize];
+        let operator = match token.kind {
+            TokenKind::Symbol => operators.get(token.as_str()),
+            _ => None
+        };
+        let operator = match operator {
+            Some(operator) if operator.priority >= min_priority => operator.clone(),
+            _ => break
+        };
+        let operator_start = *iterator;
+        *iterator += 1;
+
+        let right = match operator.arity {
+            Arity::Postfix => {
+                match operator.hook {
+                    OperatorHook::Call => Some(parse_elements(tokens, operators, iterator, parse_end, operator_start, ")", "arguments")?),
+                    OperatorHook::Index => Some(parse_index(tokens, operators, iterator, parse_end, operator_start)?),
+                    _ => None
+                }
+            }
+            _ => {
+                let right_priority = match operator.associativity {
+                    Associativity::Left => operator.priority + 1,
+                    Associativity::Right => operator.priority
+                };
+                Some(parse_expression(tokens, operators, iterator, parse_end, right_priority, Some(&token.span))?)
+            }
+        };
+
+        left = Expression {
+            expression_type: ExpressionType::Operation,
+            left: Some(Box::new(left)),
+            right: right.map(Box::new),
+            value: None,
+            operator: Some(operator),
+            span: get_tokens_span(tokens, start, *iterator)
+        };
+    }
+
+    return Ok(left);
+}
+
+/// Parses a value, a function, an object, an array, an expression in parentheses, or a prefix operator and its
+/// operand.
+fn parse_operand(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64, after: Option<&Span>) -> Result<Expression, Vec<ParseError>> {
+    check_nesting(tokens, *iterator)?;
+    if *iterator >= parse_end || is_expression_end(&tokens[*iterator as usize]) {
+        if after.is_some() {
+            return Err(vec![Diagnostic::error("Expression cannot end with an operator.").at(after)]);
+        }
+        get_token(tokens, *iterator, parse_end)?;
+        return Err(vec![Diagnostic::error("Empty expression.").at(get_token_span(tokens, *iterator))]);
+    }
+    let start = *iterator;
+    let token = &tokens[start as usize];
+
+    if token.eq("function") {
+        *iterator += 1;
+        let args = parse_function_arguments(tokens, operators, iterator, parse_end)?;
+        let scope = parse_block(tokens, operators, iterator, parse_end)?;
+        return Ok(Expression {
+            expression_type: ExpressionType::Value,
+            left: None,
+            right: None,
+            value: Some(Value {
+                value_type: ValueType::Constant,
+                variable: None,
+                constant: Some(Constant::Function(Rc::new(RefCell::new(Function {
+                    scope: Rc::new(scope),
+                    args,
+                    closure: None
+                }))))
+            }),
+            operator: None,
+            span: get_tokens_span(tokens, start, *iterator)
+        });
+    }
+
+    if token.eq("{") {
+        return parse_object(tokens, operators, iterator, parse_end);
+    }
+
+    if token.eq("[") {
+        *iterator += 1;
+        let mut array = parse_elements(tokens, operators, iterator, parse_end, start, "]", "elements")?;
+        array.expression_type = ExpressionType::Array;
+        return Ok(array);
+    }
+
+    if token.eq("(") {
+        *iterator += 1;
+        let mut expression = Expression {
+            expression_type: ExpressionType::Undefined,
+            left: None,
+            right: None,
+            value: None,
+            operator: None,
+            span: None
+        };
+        expression.parse(tokens, operators, iterator, parse_end)?;
+        if !is_token(tokens, *iterator, parse_end, ")") {
+            return Err(vec![Diagnostic::error("Expected ')'.").at(get_token_span(tokens, *iterator))
+                .with_label(Some(&token.span), "to close this")
+                ]);
         }
-        opsi += 1;
+        *iterator += 1;
+        expression.span = get_tokens_span(tokens, start, *iterator);
+        return Ok(expression);
     }
-    return 0;
+
+    if let TokenKind::Symbol = token.kind {
+        if let Some(operator) = operators.get_prefix(token.as_str()) {
+            let operator = operator.clone();
+            *iterator += 1;
+            let operand = parse_expression(tokens, operators, iterator, parse_end, operator.priority, Some(&token.span))?;
+            return Ok(Expression {
+                expression_type: ExpressionType::Operation,
+                left: None,
+                right: Some(Box::new(operand)),
+                value: None,
+                operator: Some(operator),
+                span: get_tokens_span(tokens, start, *iterator)
+            });
+        }
+        if operators.get(token.as_str()).is_some() {
+            return Err(vec![Diagnostic::error("Found operator instead of value.").at(Some(&token.span))]);
+        }
+        return Err(vec![Diagnostic::error(format!("Unexpected '{}'.", token).as_str()).at(Some(&token.span))]);
+    }
+
+    let value = match token.kind {
+        TokenKind::Number => {
+            let constant = if token.text.contains('.') {
+                token.text.parse::<f64>().ok().map(Constant::Float)
+            } else {
+                token.text.parse::<i64>().ok().map(Constant::Integer)
+            };
+            if constant.is_none() {
+                return Err(vec![Diagnostic::error(format!("Invalid number '{}'.", token).as_str()).at(Some(&token.span))]);
+            }
+            Value {
+                value_type: ValueType::Constant,
+                variable: None,
+                constant,
+            }
+        }
+        TokenKind::String => {
+            Value {
+                value_type: ValueType::Constant,
+                variable: None,
+                constant: Some(Constant::String(token.text.clone())),
+            }
+        }
+        TokenKind::UnterminatedString => {
+            return Err(vec![Diagnostic::error("Unterminated string.").at(Some(&token.span))
+                .with_help("a string ends with the same quote it starts with")
+                ]);
+        }
+        _ => {
+            let keyword_constant = match token.as_str() {
+                "true" => Some(Constant::Boolean(true)),
+                "false" => Some(Constant::Boolean(false)),
+                "null" => Some(Constant::Null),
+                "undefined" => Some(Constant::Undefined),
+                _ => None
+            };
+            match keyword_constant {
+                Some(constant) => Value {
+                    value_type: ValueType::Constant,
+                    variable: None,
+                    constant: Some(constant),
+                },
+                None => Value {
+                    value_type: ValueType::VariableName,
+                    variable: Some(token.to_string()),
+                    constant: None
+                }
+            }
+        }
+    };
+
+    *iterator += 1;
+    return Ok(Expression {
+        expression_type: ExpressionType::Value,
+        left: None,
+        right: None,
+        value: Some(value),
+        operator: None,
+        span: Some(token.span.clone())
+    });
 }
 
-fn operator_exists(operators: &Vec<String>, operator: &String) -> bool {
-    for op_search in operators.iter() {
-        if op_search.eq(operator) {
-            return true;
+/// Parses the arguments of a call or the elements of an array into a tuple, starting after the opening symbol and
+/// stopping after the closing one.
+///
+/// `name` is what the expressions are called in errors.
+fn parse_elements(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64, start: i64, closing: &str, name: &str) -> Result<Expression, Vec<ParseError>> {
+    let mut expressions: Vec<Expression> = vec![];
+    while !is_token(tokens, *iterator, parse_end, closing) {
+        let mut expression = Expression {
+            expression_type: ExpressionType::Undefined,
+            left: None,
+            right: None,
+            value: None,
+            operator: None,
+            span: None
+        };
+        expression.parse(tokens, operators, iterator, parse_end)?;
+        expressions.push(expression);
+
+        let token = get_token(tokens, *iterator, parse_end)?;
+        if token.eq(",") {
+            *iterator += 1;
+        } else if !token.eq(closing) {
+            return Err(vec![Diagnostic::error("Expected operator ','.").at(Some(&token.span))
+                .with_label(get_token_span(tokens, start), format!("the {} start here", name).as_str())
+                .with_help(format!("{} are separated by ','", name).as_str())
+                ]);
         }
     }
+    *iterator += 1;
+
+    return Ok(Expression {
+        expression_type: ExpressionType::Value,
+        left: None,
+        right: None,
+        value: Some(Value {
+            value_type: ValueType::Constant,
+            variable: None,
+            constant: Some(Constant::Tuple(Rc::new(RefCell::new(Tuple {
+                expressions
+            }))))
+        }),
+        operator: None,
+        span: get_tokens_span(tokens, start, *iterator)
+    });
+}
 
-    return false;
-}
\ No newline at end of file
+/// Parses the expression naming the member in `object[expression]`, starting after the opening bracket and stopping
+/// after the closing one.
+fn parse_index(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64, start: i64) -> Result<Expression, Vec<ParseError>> {
+    let mut expression = Expression {
+        expression_type: ExpressionType::Undefined,
+        left: None,
+        right: None,
+        value: None,
+        operator: None,
+        span: None
+    };
+    expression.parse(tokens, operators, iterator, parse_end)?;
+    if !is_token(tokens, *iterator, parse_end, "]") {
+        return Err(vec![Diagnostic::error("Expected ']'.").at(get_token_span(tokens, *iterator))
+            .with_label(get_token_span(tokens, start), "to close this")
+            ]);
+    }
+    *iterator += 1;
+    return Ok(expression);
+}
+
+/// Parses `{ name: value, ... }`, starting on the opening brace and stopping after the closing one.
+///
+/// Member names are identifiers or strings, and the last member can be followed by a ','.
+fn parse_object(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<Expression, Vec<ParseError>> {
+    let start = *iterator;
+    *iterator += 1;
+
+    let mut members: Vec<Expression> = vec![];
+    while !is_token(tokens, *iterator, parse_end, "}") {
+        let member_start = *iterator;
+        let token = get_token(tokens, *iterator, parse_end)?;
+        if !matches!(token.kind, TokenKind::Identifier | TokenKind::String) {
+            return Err(vec![Diagnostic::error("Expected member name.").at(Some(&token.span))
+                .with_label(get_token_span(tokens, start), "the object starts here")
+                ]);
+        }
+        let name = Expression {
+            expression_type: ExpressionType::Value,
+            left: None,
+            right: None,
+            value: Some(Value {
+                value_type: ValueType::VariableName,
+                variable: Some(token.text.clone()),
+                constant: None
+            }),
+            operator: None,
+            span: Some(token.span.clone())
+        };
+        *iterator += 1;
+        expect_token(tokens, ":", iterator, parse_end)?;
+
+        let mut value = Expression {
+            expression_type: ExpressionType::Undefined,
+            left: None,
+            right: None,
+            value: None,
+            operator: None,
+            span: None
+        };
+        value.parse(tokens, operators, iterator, parse_end)?;
+        members.push(Expression {
+            expression_type: ExpressionType::Operation,
+            left: Some(Box::new(name)),
+            right: Some(Box::new(value)),
+            value: None,
+            operator: operators.get("=").cloned(),
+            span: get_tokens_span(tokens, member_start, *iterator)
+        });
+
+        let token = get_token(tokens, *iterator, parse_end)?;
+        if token.eq(",") {
+            *iterator += 1;
+        } else if !token.eq("}") {
+            return Err(vec![Diagnostic::error("Expected operator ','.").at(Some(&token.span))
+                .with_label(get_token_span(tokens, start), "the object starts here")
+                .with_help("members are separated by ','")
+                ]);
+        }
+    }
+    *iterator += 1;
+
+    return Ok(Expression {
+        expression_type: ExpressionType::Object,
+        left: None,
+        right: None,
+        value: Some(Value {
+            value_type: ValueType::Constant,
+            variable: None,
+            constant: Some(Constant::Tuple(Rc::new(RefCell::new(Tuple {
+                expressions: members
+            }))))
+        }),
+        operator: None,
+        span: get_tokens_span(tokens, start, *iterator)
+    });
+}
+
+/// Whether the token ends the expression before it.
+fn is_expression_end(token: &Token) -> bool {
+    return token.is(";") || token.is(",") || token.is(")") || token.is("]") || token.is("}");
+}
+
+/// Parses `(a, b, c)`, starting on the opening parenthesis and stopping after the closing one.
+fn parse_function_arguments(tokens: &Vec<Token>, _operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<Vec<String>, Vec<ParseError>> {
+    if !is_token(tokens, *iterator, parse_end, "(") {
+        return Err(vec![Diagnostic::error("Expected '('.").at(get_token_span(tokens, *iterator))]);
+    }
+    let start = *iterator;
+    *iterator += 1;
+
+    let mut args: Vec<String> = vec![];
+    let mut expected_variable_name = true;
+    let mut token = get_token(tokens, *iterator, parse_end)?;
+    while !token.eq(")") {
+        if expected_variable_name {
+            if !matches!(token.kind, TokenKind::Identifier) {
+                return Err(vec![Diagnostic::error("Expected variable name.").at(Some(&token.span))]);
+            }
+            args.push(token.text.clone());
+        }
+        else if !token.eq(",") {
+            return Err(vec![Diagnostic::error("Expected operator ','.").at(Some(&token.span))
+                .with_label(get_token_span(tokens, start), "the arguments start here")
+                .with_help("arguments are separated by ','")
+                ]);
+        }
+        expected_variable_name = !expected_variable_name;
+
+        *iterator += 1;
+        token = get_token(tokens, *iterator, parse_end)?;
+    }
+
+    *iterator += 1;
+    return Ok(args);
+}
+
+/// Skips the expected token, which has to be the current one.
+fn expect_token(tokens: &Vec<Token>, expected: &str, iterator: &mut i64, parse_end: i64) -> Result<(), Vec<ParseError>> {
+    if !is_token(tokens, *iterator, parse_end, expected) {
+        return Err(vec![Diagnostic::error(format!("Expected '{}'.", expected).as_str()).at(get_token_span(tokens, *iterator))]);
+    }
+    *iterator += 1;
+    return Ok(());
+}
+
+/// Parses `( expression )`, starting on the opening parenthesis and stopping after the closing one.
+fn parse_condition(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<Expression, Vec<ParseError>> {
+    if !is_token(tokens, *iterator, parse_end, "(") {
+        return Err(vec![Diagnostic::error("Expected '('.").at(get_token_span(tokens, *iterator))]);
+    }
+    let start = *iterator;
+    *iterator += 1;
+
+    let mut condition = Expression {
+        expression_type: ExpressionType::Undefined,
+        left: None,
+        right: None,
+        value: None,
+        operator: None,
+        span: None
+    };
+    condition.parse(tokens, operators, iterator, parse_end)?;
+
+    if !is_token(tokens, *iterator, parse_end, ")") {
+        return Err(vec![Diagnostic::error("Expected ')'.").at(get_token_span(tokens, *iterator))
+            .with_label(get_token_span(tokens, start), "to close this")
+            ]);
+    }
+    *iterator += 1;
+    return Ok(condition);
+}
+
+/// Parses `{ ... }`, starting on the opening brace and stopping after the closing one.
+///
+/// The closing brace is skipped even if the block has errors, so the caller can carry on after it.
+fn parse_block(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Result<Scope, Vec<ParseError>> {
+    check_nesting(tokens, *iterator)?;
+    if !is_token(tokens, *iterator, parse_end, "{") {
+        return Err(vec![Diagnostic::error("Expected '{'.").at(get_token_span(tokens, *iterator))]);
+    }
+    *iterator += 1;
+
+    let start = *iterator - 1;
+    let result = Scope::parse(tokens, operators, iterator, parse_end);
+
+    if *iterator >= parse_end {
+        // An error inside the block already explains why it wasn't closed.
+        return Err(result.err().unwrap_or_else(|| vec![Diagnostic::error("Expected '}'.").at(get_token_span(tokens, *iterator))
+            .with_label(get_token_span(tokens, start), "the block starts here")
+            ]));
+    }
+    *iterator += 1;
+    let mut scope = result?;
+    scope.span = get_tokens_span(tokens, start, *iterator);
+    return Ok(scope);
+}
+
+/// The error of an operator declaration without a valid priority after its arguments, `index` being the token following
+/// them.
+fn get_missing_priority_error(tokens: &Vec<Token>, index: i64, symbol: &str) -> ParseError {
+    return Diagnostic::error(format!("Expected 'priority <n>' after the arguments of operator '{}'.", symbol).as_str()).at(get_token_span(tokens, index))
+        .with_help("for example 'operator <=> (a, b) priority -2 { ... }'");
+}
+
+/// The `(name)` following `catch`, if there is one.
+fn parse_catch_binding(tokens: &Vec<Token>, iterator: &mut i64, parse_end: i64) -> Result<Option<String>, Vec<ParseError>> {
+    if !is_token(tokens, *iterator, parse_end, "(") {
+        return Ok(None);
+    }
+    *iterator += 1;
+    let token = get_token(tokens, *iterator, parse_end)?;
+    if !matches!(token.kind, TokenKind::Identifier) {
+        return Err(vec![Diagnostic::error("Expected variable name.").at(Some(&token.span))]);
+    }
+    *iterator += 1;
+    expect_token(tokens, ")", iterator, parse_end)?;
+    return Ok(Some(token.text.clone()));
+}
+
+/// Skips what is left of a statement that failed to parse after starting at `statement_start`.
+///
+/// A block with errors is already skipped up to its '}', only the other blocks of an if or a try can follow it.
+fn skip_failed_statement(tokens: &Vec<Token>, iterator: &mut i64, parse_end: i64, statement_start: i64) {
+    if *iterator == statement_start || !is_token(tokens, *iterator - 1, parse_end, "}") {
+        let depth = get_open_braces(tokens, statement_start, *iterator);
+        skip_statement(tokens, iterator, parse_end, depth);
+    }
+}
+
+/// Parses the `else`, `catch` and `finally` blocks following an if or a try that failed to parse, returning their
+/// errors. The statement is lost anyway, but the errors in these blocks are reported with the others.
+///
+/// An `else if` is left to be parsed as an if statement of its own.
+fn parse_remaining_blocks(tokens: &Vec<Token>, operators: &OperatorTable, iterator: &mut i64, parse_end: i64) -> Vec<ParseError> {
+    let mut errors: Vec<ParseError> = vec![];
+    loop {
+        let clause_start = *iterator;
+        let result = if is_token(tokens, *iterator, parse_end, "else") {
+            *iterator += 1;
+            if is_token(tokens, *iterator, parse_end, "if") {
+                break;
+            }
+            parse_block(tokens, operators, iterator, parse_end)
+        } else if is_token(tokens, *iterator, parse_end, "catch") {
+            *iterator += 1;
+            parse_catch_binding(tokens, iterator, parse_end).and_then(|_| parse_block(tokens, operators, iterator, parse_end))
+        } else if is_token(tokens, *iterator, parse_end, "finally") {
+            *iterator += 1;
+            parse_block(tokens, operators, iterator, parse_end)
+        } else {
+            break;
+        };
+
+        if let Err(mut block_errors) = result {
+            errors.append(&mut block_errors);
+            skip_failed_statement(tokens, iterator, parse_end, clause_start);
+        }
+    }
+    return errors;
+}
+
+/// Skips the rest of a statement that failed to parse, stopping after its `;` or after a block it contains, or before
+/// the `}` closing the enclosing block.
+///
+/// `depth` is the number of braces the statement opened before the error, like the one of an object it stopped in.
+fn skip_statement(tokens: &Vec<Token>, iterator: &mut i64, parse_end: i64, mut depth: i64) {
+    while *iterator < parse_end {
+        let token = &tokens[*iterator as usize];
+        if token.eq("{") {
+            depth += 1;
+        } else if token.eq("}") {
+            if depth == 0 {
+                return;
+            }
+            depth -= 1;
+            if depth == 0 {
+                *iterator += 1;
+                return;
+            }
+        } else if token.eq(";") && depth == 0 {
+            *iterator += 1;
+            return;
+        }
+        *iterator += 1;
+    }
+}
+
+/// How many of the braces opened between the two tokens are still open after them.
+fn get_open_braces(tokens: &Vec<Token>, start: i64, end: i64) -> i64 {
+    let mut open = 0;
+    for token in tokens[start as usize..end.min(tokens.len() as i64) as usize].iter() {
+        if token.eq("{") {
+            open += 1;
+        } else if token.eq("}") && open > 0 {
+            open -= 1;
+        }
+    }
+    return open;
+}
+
+/// The span going from the first token to the one before `end`, if there is at least one.
+fn get_tokens_span(tokens: &Vec<Token>, start: i64, end: i64) -> Option<Span> {
+    if start >= end || end as usize > tokens.len() {
+        return None;
+    }
+    return Some(tokens[start as usize].span.to(&tokens[end as usize - 1].span));
+}
+
+/// The token at the index, or an error if the source ended before it.
+fn get_token(tokens: &Vec<Token>, index: i64, parse_end: i64) -> Result<&Token, Vec<ParseError>> {
+    if index < parse_end {
+        if let Some(token) = tokens.get(index as usize) {
+            return Ok(token);
+        }
+    }
+    return Err(vec![Diagnostic::error("Unexpected end of input.").at(tokens.last().map(|token| &token.span))]);
+}
+
+/// Whether the token at the index is the given symbol or word, never true past the end.
+fn is_token(tokens: &Vec<Token>, index: i64, parse_end: i64, text: &str) -> bool {
+    return index < parse_end && tokens.get(index as usize).is_some_and(|token| token.is(text));
+}
+
+/// The span of a token, or of the last one when the source ended before it.
+const NESTING_ERROR: &str = "Source nested too deeply.";
+
+/// Fails once the blocks and expressions around the token are nested deep enough to use more of the stack than the
+/// interpreter allows, before the thread runs out of it.
+fn check_nesting(tokens: &Vec<Token>, index: i64) -> Result<(), Vec<ParseError>> {
+    if is_stack_exhausted() {
+        return Err(vec![Diagnostic::error(NESTING_ERROR).at(get_token_span(tokens, index))]);
+    }
+    return Ok(());
+}
+
+/// Whether parsing stopped on a source nested too deeply, in which case nothing more can be parsed after it.
+fn is_nesting_error(errors: &Vec<ParseError>) -> bool {
+    return errors.iter().any(|error| error.message == NESTING_ERROR);
+}
+
+fn get_token_span(tokens: &Vec<Token>, index: i64) -> Option<&Span> {
+    return match tokens.get(index as usize) {
+        Some(token) => Some(&token.span),
+        None => tokens.last().map(|token| &token.span)
+    };
+}
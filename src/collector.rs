@@ -0,0 +1,246 @@
+use crate::abstract_syntax_tree::Constant;
+use crate::executor::{Variable, Callable, Environment};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::ops::Range;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+/// The members of an object or the elements of an array.
+type Members = Rc<RefCell<Vec<Rc<RefCell<Variable>>>>>;
+
+/// How many candidates are kept before the first collection.
+const MIN_CANDIDATES: usize = 1024;
+
+/// Something that can hold a reference back to itself through the values it contains: an environment captured by a
+/// function, or the members of an object or an array.
+enum Candidate {
+    Environment(Weak<RefCell<Environment>>),
+    Members(Weak<RefCell<Vec<Rc<RefCell<Variable>>>>>),
+}
+
+thread_local! {
+    static CANDIDATES: RefCell<Vec<Candidate>> = const { RefCell::new(vec![]) };
+    /// How many candidates there can be before the next collection, twice as many as the last one left so that the
+    /// time spent collecting stays proportional to the values created.
+    static MAX_CANDIDATES: Cell<usize> = const { Cell::new(MIN_CANDIDATES) };
+}
+
+/// Watches an environment captured by a function, which the function can be stored in.
+pub fn add_environment(environment: &Rc<RefCell<Environment>>) {
+    add_candidate(Candidate::Environment(Rc::downgrade(environment)));
+}
+
+/// Watches the members of a new object or the elements of a new array, which can end up containing themselves.
+pub fn add_members(members: &Members) {
+    add_candidate(Candidate::Members(Rc::downgrade(members)));
+}
+
+fn add_candidate(candidate: Candidate) {
+    let count = CANDIDATES.with(|candidates| {
+        let mut candidates = candidates.borrow_mut();
+        candidates.push(candidate);
+        candidates.len()
+    });
+    if count >= MAX_CANDIDATES.with(|max_candidates| max_candidates.get()) {
+        collect();
+        let count = CANDIDATES.with(|candidates| candidates.borrow().len());
+        MAX_CANDIDATES.with(|max_candidates| max_candidates.set(MIN_CANDIDATES.max(count * 2)));
+    }
+}
+
+/// A value holding references to others, sharing it with the value it comes from.
+enum Node {
+    Environment(Rc<RefCell<Environment>>),
+    Variable(Rc<RefCell<Variable>>),
+    Function(Rc<RefCell<dyn Callable>>),
+    Members(Members),
+}
+
+impl Node {
+    fn get_address(&self) -> usize {
+        return match self {
+            Node::Environment(environment) => Rc::as_ptr(environment) as *const () as usize,
+            Node::Variable(variable) => Rc::as_ptr(variable) as *const () as usize,
+            Node::Function(function) => Rc::as_ptr(function) as *const () as usize,
+            Node::Members(members) => Rc::as_ptr(members) as *const () as usize
+        };
+    }
+
+    fn get_reference_count(&self) -> usize {
+        return match self {
+            Node::Environment(environment) => Rc::strong_count(environment),
+            Node::Variable(variable) => Rc::strong_count(variable),
+            Node::Function(function) => Rc::strong_count(function),
+            Node::Members(members) => Rc::strong_count(members)
+        };
+    }
+
+    /// Adds the values this one references to the list, or returns false if it is being changed and can't be looked
+    /// into.
+    fn add_children(&self, children: &mut Vec<Node>) -> bool {
+        match self {
+            Node::Environment(environment) => {
+                let environment = match environment.try_borrow() {
+                    Ok(environment) => environment,
+                    Err(_) => return false
+                };
+                children.extend(environment.variables.iter().map(|variable| Node::Variable(variable.clone())));
+                children.extend(environment.parent.clone().map(Node::Environment));
+            }
+            Node::Variable(variable) => {
+                let variable = match variable.try_borrow() {
+                    Ok(variable) => variable,
+                    Err(_) => return false
+                };
+                match &variable.constant {
+                    Some(Constant::Function(function)) => children.push(Node::Function(function.clone())),
+                    Some(Constant::Object(members)) | Some(Constant::Array(members)) => children.push(Node::Members(members.clone())),
+                    _ => {}
+                }
+            }
+            Node::Function(function) => {
+                let function = match function.try_borrow() {
+                    Ok(function) => function,
+                    Err(_) => return false
+                };
+                children.extend(function.get_closure().map(Node::Environment));
+            }
+            Node::Members(members) => {
+                let members = match members.try_borrow() {
+                    Ok(members) => members,
+                    Err(_) => return false
+                };
+                children.extend(members.iter().map(|member| Node::Variable(member.clone())));
+            }
+        }
+        return true;
+    }
+}
+
+struct NodeInfo {
+    node: Node,
+    /// Where the addresses of the values it references are in the list of references, `None` if they couldn't be
+    /// found.
+    references: Option<Range<usize>>,
+    /// How many references to it come from the other values found.
+    internal_references: usize,
+    is_reachable: bool,
+}
+
+/// Hashes the addresses of values, which are already unique.
+#[derive(Default)]
+struct AddressHasher(u64);
+
+impl Hasher for AddressHasher {
+    fn finish(&self) -> u64 {
+        return self.0;
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 << 8 | *byte as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        }
+    }
+
+    fn write_usize(&mut self, address: usize) {
+        self.0 = (address as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+}
+
+type AddressMap<T> = HashMap<usize, T, BuildHasherDefault<AddressHasher>>;
+
+/// Frees the candidates, and the values they hold, only referenced by cycles among themselves.
+///
+/// Every value reachable from a candidate is found, along with how many of the references to it come from those
+/// values. A value with more references than that is used from elsewhere, by the interpreter or a running call, so it
+/// and all it holds are kept. The environments and the members of the others are emptied, which breaks their cycles.
+pub fn collect() {
+    let candidates = CANDIDATES.with(|candidates| mem::take(&mut *candidates.borrow_mut()));
+    let mut pending: Vec<Node> = vec![];
+    for candidate in candidates.iter() {
+        match candidate {
+            Candidate::Environment(environment) => pending.extend(environment.upgrade().map(Node::Environment)),
+            Candidate::Members(members) => pending.extend(members.upgrade().map(Node::Members))
+        }
+    }
+
+    let mut indices: AddressMap<usize> = AddressMap::default();
+    let mut nodes: Vec<NodeInfo> = vec![];
+    let mut references: Vec<usize> = vec![];
+    while let Some(node) = pending.pop() {
+        let address = node.get_address();
+        if indices.contains_key(&address) {
+            continue;
+        }
+        let children_start = pending.len();
+        let found = node.add_children(&mut pending);
+        let references_start = references.len();
+        references.extend(pending[children_start..].iter().map(|child| child.get_address()));
+        indices.insert(address, nodes.len());
+        nodes.push(NodeInfo {
+            node,
+            references: if found { Some(references_start..references.len()) } else { None },
+            internal_references: 0,
+            is_reachable: false,
+        });
+    }
+
+    for address in references.iter() {
+        nodes[indices[address]].internal_references += 1;
+    }
+
+    // The collector holds one of the references itself.
+    let mut reachable: Vec<usize> = vec![];
+    for (index, info) in nodes.iter().enumerate() {
+        if info.references.is_none() || info.node.get_reference_count() > info.internal_references + 1 {
+            reachable.push(index);
+        }
+    }
+    while let Some(index) = reachable.pop() {
+        let info = &mut nodes[index];
+        if info.is_reachable {
+            continue;
+        }
+        info.is_reachable = true;
+        if let Some(range) = info.references.clone() {
+            reachable.extend(references[range].iter().map(|address| indices[address]));
+        }
+    }
+
+    // Emptied values are only dropped once none of them is borrowed anymore.
+    let mut garbage: Vec<Vec<Rc<RefCell<Variable>>>> = vec![];
+    let mut garbage_parents: Vec<Rc<RefCell<Environment>>> = vec![];
+    for info in nodes.iter().filter(|info| !info.is_reachable) {
+        match &info.node {
+            Node::Environment(environment) => {
+                if let Ok(mut environment) = environment.try_borrow_mut() {
+                    garbage.push(mem::take(&mut environment.variables));
+                    garbage_parents.extend(environment.parent.take());
+                }
+            }
+            Node::Members(members) => {
+                if let Ok(mut members) = members.try_borrow_mut() {
+                    garbage.push(mem::take(&mut *members));
+                }
+            }
+            Node::Variable(_) | Node::Function(_) => {}
+        }
+    }
+    drop(nodes);
+    drop(garbage);
+    drop(garbage_parents);
+
+    // The values still alive can end up in a cycle later, once each.
+    let mut addresses: AddressMap<()> = AddressMap::default();
+    let remaining: Vec<Candidate> = candidates.into_iter().filter(|candidate| match candidate {
+        Candidate::Environment(environment) => environment.strong_count() > 0 && addresses.insert(environment.as_ptr() as usize, ()).is_none(),
+        Candidate::Members(members) => members.strong_count() > 0 && addresses.insert(members.as_ptr() as usize, ()).is_none()
+    }).collect();
+    CANDIDATES.with(|candidates| {
+        let mut candidates = candidates.borrow_mut();
+        let mut added = mem::replace(&mut *candidates, remaining);
+        candidates.append(&mut added);
+    });
+}
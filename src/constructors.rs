@@ -1,80 +1,158 @@
-use crate::executor::{Variable, Callable};
-use crate::abstract_syntax_tree::{Value, ValueType, Constant, Scope, Function, Tuple};
+use crate::executor::{Variable, Callable, Environment};
+use crate::abstract_syntax_tree::{Value, ValueType, Constant, Tuple};
+use crate::collector;
 use std::cell::{RefCell};
 use std::rc::Rc;
 
 pub fn construct_variable_from_integer(integer: i64) -> Rc<RefCell<Variable>> {
     return Rc::new(RefCell::new(Variable{
         name: None,
-        constant: Some(Constant::Integer(integer)),
-        members: vec![]
+        constant: Some(Constant::Integer(integer))
     }));
 }
 
-pub fn construct_variable_from_function(function: Rc<RefCell<Callable>>) -> Rc<RefCell<Variable>> {
+pub fn construct_variable_from_float(float: f64) -> Rc<RefCell<Variable>> {
+    return Rc::new(RefCell::new(Variable{
+        name: None,
+        constant: Some(Constant::Float(float))
+    }));
+}
+
+pub fn construct_variable_from_string(string: String) -> Rc<RefCell<Variable>> {
+    return Rc::new(RefCell::new(Variable{
+        name: None,
+        constant: Some(Constant::String(string))
+    }));
+}
+
+pub fn construct_variable_from_undefined() -> Rc<RefCell<Variable>> {
+    return Rc::new(RefCell::new(Variable{
+        name: None,
+        constant: Some(Constant::Undefined)
+    }));
+}
+
+pub fn construct_variable_from_null() -> Rc<RefCell<Variable>> {
+    return Rc::new(RefCell::new(Variable{
+        name: None,
+        constant: Some(Constant::Null)
+    }));
+}
+
+pub fn construct_variable_from_boolean(boolean: bool) -> Rc<RefCell<Variable>> {
+    return Rc::new(RefCell::new(Variable{
+        name: None,
+        constant: Some(Constant::Boolean(boolean))
+    }));
+}
+
+pub fn construct_variable_from_function(function: Rc<RefCell<dyn Callable>>) -> Rc<RefCell<Variable>> {
     return Rc::new(RefCell::new(Variable {
         name: None,
-        constant: Some(Constant::Function(function)),
-        members: vec![]
+        constant: Some(Constant::Function(function))
     }))
 }
 
 pub fn construct_variable_from_tuple(tuple: Rc<RefCell<Tuple>>) -> Rc<RefCell<Variable>> {
     return Rc::new(RefCell::new(Variable {
         name: None,
-        constant: Some(Constant::Tuple(tuple)),
-        members: vec![]
+        constant: Some(Constant::Tuple(tuple))
+    }));
+}
+
+pub fn construct_variable_from_object(members: Vec<Rc<RefCell<Variable>>>) -> Rc<RefCell<Variable>> {
+    let members = Rc::new(RefCell::new(members));
+    collector::add_members(&members);
+    return Rc::new(RefCell::new(Variable {
+        name: None,
+        constant: Some(Constant::Object(members))
+    }));
+}
+
+/// An array holding the given elements, which shouldn't be shared with other arrays or variables.
+pub fn construct_variable_from_array(elements: Vec<Rc<RefCell<Variable>>>) -> Rc<RefCell<Variable>> {
+    let elements = Rc::new(RefCell::new(elements));
+    collector::add_members(&elements);
+    return Rc::new(RefCell::new(Variable {
+        name: None,
+        constant: Some(Constant::Array(elements))
     }));
 }
 
-pub fn construct_variable(value: Value, scope: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>> {
+/// An `Error` object, its stack is filled in when it is caught.
+pub fn construct_variable_from_error(message: String) -> Rc<RefCell<Variable>> {
+    return construct_variable_from_object(vec![
+        Rc::new(RefCell::new(Variable {
+            name: Some("message".to_string()),
+            constant: Some(Constant::String(message))
+        })),
+        Rc::new(RefCell::new(Variable {
+            name: Some("stack".to_string()),
+            constant: Some(Constant::String("".to_string()))
+        }))
+    ]);
+}
+
+pub fn construct_variable(value: Value, environment: Rc<RefCell<Environment>>) -> Rc<RefCell<Variable>> {
     match value.value_type {
         ValueType::Undefined => {
             return Rc::new(RefCell::new(Variable {
-                members: vec![],
                 name: None,
                 constant: Some(Constant::Undefined),
             }));
         }
         ValueType::VariableName => {
-            fn get_variable_by_name(scope: Rc<RefCell<Scope>>, name: &str) -> Rc<RefCell<Variable>> {
-                for accessible_var in (*scope).borrow().accessible_variables.iter() {
-                    if (*accessible_var).borrow_mut().name.as_ref().unwrap().eq(name) {
-                        return accessible_var.clone();
-                    }
-                }
-
-                if (*scope).borrow().parent.is_some() {
-                    return get_variable_by_name((*scope).borrow().parent.as_ref().unwrap().clone(), name);
-                }
-
-                return Rc::new(RefCell::new(Variable {
-                    members: vec![],
-                    name: Some(name.to_string()),
-                    constant: Some(Constant::Undefined),
-                }));
+            let name = value.variable.as_ref().unwrap();
+            if let Some(variable) = (*environment).borrow().get_variable(name) {
+                return variable;
             }
 
-            return get_variable_by_name(scope, value.variable.as_ref().unwrap().as_str());
+            return Rc::new(RefCell::new(Variable {
+                name: Some(name.clone()),
+                constant: Some(Constant::Undefined),
+            }));
         }
         ValueType::Constant => {
             match value.constant.as_ref().unwrap() {
                 Constant::Undefined => {
                     return Rc::new(RefCell::new(Variable {
-                        members: vec![],
                         name: None,
                         constant: Some(Constant::Undefined),
                     }));
                 },
+                Constant::Null => {
+                    return construct_variable_from_null();
+                },
                 Constant::Integer(i) => {
                     return construct_variable_from_integer(*i);
                 },
+                Constant::Float(f) => {
+                    return construct_variable_from_float(*f);
+                },
+                Constant::Boolean(b) => {
+                    return construct_variable_from_boolean(*b);
+                },
+                Constant::String(s) => {
+                    return construct_variable_from_string(s.clone());
+                },
                 Constant::Function(f) => {
                     return construct_variable_from_function(f.clone());
                 }
                 Constant::Tuple(t) => {
                     return construct_variable_from_tuple(t.clone());
                 }
+                Constant::Object(o) => {
+                    return Rc::new(RefCell::new(Variable {
+                        name: None,
+                        constant: Some(Constant::Object(o.clone()))
+                    }));
+                }
+                Constant::Array(a) => {
+                    return Rc::new(RefCell::new(Variable {
+                        name: None,
+                        constant: Some(Constant::Array(a.clone()))
+                    }));
+                }
             }
         }
     }
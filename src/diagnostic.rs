@@ -0,0 +1,163 @@
+use crate::lexer::Span;
+use std::fmt;
+
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Copy for Severity {}
+
+impl Clone for Severity {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Severity {
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning"
+        };
+    }
+}
+
+/// A secondary location shown next to the main one, explaining how it relates to the problem.
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Clone for Label {
+    fn clone(&self) -> Self {
+        return Label {
+            span: self.span.clone(),
+            message: self.message.clone(),
+        };
+    }
+}
+
+/// A problem found in a script, rendered with the source lines it points at.
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub help: Vec<String>,
+}
+
+impl Clone for Diagnostic {
+    fn clone(&self) -> Self {
+        return Diagnostic {
+            severity: self.severity,
+            message: self.message.clone(),
+            span: self.span.clone(),
+            labels: self.labels.clone(),
+            help: self.help.clone(),
+        };
+    }
+}
+
+impl Diagnostic {
+    pub fn error(message: &str) -> Diagnostic {
+        return Diagnostic {
+            severity: Severity::Error,
+            message: message.to_string(),
+            span: None,
+            labels: vec![],
+            help: vec![],
+        };
+    }
+
+    pub fn at(mut self, span: Option<&Span>) -> Diagnostic {
+        self.span = span.cloned();
+        return self;
+    }
+
+    /// Adds a secondary location, nothing is added without a span.
+    pub fn with_label(mut self, span: Option<&Span>, message: &str) -> Diagnostic {
+        if let Some(span) = span {
+            self.labels.push(Label {
+                span: span.clone(),
+                message: message.to_string(),
+            });
+        }
+        return self;
+    }
+
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help.push(help.to_string());
+        return self;
+    }
+
+    /// Renders the diagnostic like:
+    ///
+    /// ```text
+    /// error: Expected operator ','.
+    ///  --> script.sys:1:20
+    ///   |
+    /// 1 | let f = function(a b) {};
+    ///   |                  - the arguments start here
+    ///   |                    ^
+    ///   = help: arguments are separated by ','
+    /// ```
+    pub fn render(&self) -> String {
+        let mut result = format!("{}: {}", self.severity.get_name(), self.message);
+
+        let span = match &self.span {
+            Some(span) => span,
+            None => {
+                for help in self.help.iter() {
+                    result.push_str(format!("\n  = help: {}", help).as_str());
+                }
+                return result;
+            }
+        };
+
+        // The primary span then the labels, each one underlined below its line.
+        let mut annotations: Vec<(&Span, char, &str)> = vec![(span, '^', "")];
+        for label in self.labels.iter() {
+            annotations.push((&label.span, '-', label.message.as_str()));
+        }
+        annotations.sort_by_key(|(span, _, _)| (span.line, span.column));
+
+        let gutter_width = annotations.iter().map(|(span, _, _)| span.line.to_string().len()).max().unwrap();
+        let gutter = " ".repeat(gutter_width);
+
+        result.push_str(format!("\n{}--> {}", gutter, span).as_str());
+        result.push_str(format!("\n{} |", gutter).as_str());
+
+        let mut previous_line = 0;
+        for (span, underline, message) in annotations.iter() {
+            let line = span.source.text.lines().nth(span.line - 1).unwrap_or("");
+            if span.line != previous_line {
+                result.push_str(format!("\n{:>width$} | {}", span.line, line, width = gutter_width).as_str());
+                previous_line = span.line;
+            }
+
+            // Tabs are kept so the underline lines up with the source whatever their width.
+            let indent: String = line.chars().take(span.column - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+            let line_end = span.source.text[span.start..].find('\n').map(|length| span.start + length).unwrap_or(span.source.text.len());
+            let end = if span.end < line_end { span.end } else { line_end };
+            let width = span.source.text[span.start..end].chars().count().max(1);
+
+            result.push_str(format!("\n{} | {}{}", gutter, indent, underline.to_string().repeat(width)).as_str());
+            if !message.is_empty() {
+                result.push(' ');
+                result.push_str(message);
+            }
+        }
+
+        for help in self.help.iter() {
+            result.push_str(format!("\n{} = help: {}", gutter, help).as_str());
+        }
+        return result;
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.render());
+    }
+}
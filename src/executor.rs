@@ -1,15 +1,18 @@
-use crate::abstract_syntax_tree::{Expression, ExpressionType, Value, ValueType, Constant, Dumpable, Statement, Scope, Function, Tuple};
-use std::borrow::{Borrow};
-use crate::constructors::construct_variable;
-use std::cell::RefCell;
+use crate::abstract_syntax_tree::{Expression, ExpressionType, Value, ValueType, Constant, Dumpable, Statement, StatementKind, Scope, Function, Tuple, ObjectMembers, ArrayElements};
+use crate::constructors::{construct_variable, construct_variable_from_boolean, construct_variable_from_string, construct_variable_from_undefined, construct_variable_from_error, construct_variable_from_object, construct_variable_from_array, construct_variable_from_integer, construct_variable_from_function};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::ops::Deref;
-use crate::abstract_syntax_tree::ValueType::VariableName;
+use std::cmp::Ordering;
+use std::fmt;
+use crate::operators::{Operator, OperatorHook};
+use crate::diagnostic::Diagnostic;
+use crate::collector;
+use crate::lexer::Span;
 
 pub struct Variable {
     pub name: Option<String>,
     pub constant: Option<Constant>,
-    pub members: Vec<Rc<RefCell<Variable>>>,
 }
 
 impl Clone for Variable {
@@ -17,13 +20,27 @@ impl Clone for Variable {
         return Variable {
             name: self.name.clone(),
             constant: self.constant.clone(),
-            members: self.members.clone(),
         };
     }
 }
 
 impl Dumpable for Variable {
     fn get_dump(&self) -> String {
+        return self.get_dump_inside(&mut vec![]);
+    }
+
+    fn dump(&self) {
+        println!("{}", self.get_dump());
+    }
+}
+
+impl Variable {
+    /// The dump of the value alone, with the members of objects.
+    pub fn get_value_dump(&self) -> String {
+        return self.get_value_dump_inside(&mut vec![]);
+    }
+
+    fn get_dump_inside(&self, containers: &mut Vec<usize>) -> String {
         let mut result = "".to_string();
         if self.name.is_some() {
             result.push_str(self.name.as_ref().unwrap().as_str());
@@ -31,442 +48,1527 @@ impl Dumpable for Variable {
             result.push_str("nameless");
         }
         result.push_str(" : ");
-        if self.constant.is_some() {
-            result.push_str(self.constant.as_ref().unwrap().get_dump().as_str());
+        result.push_str(self.get_value_dump_inside(containers).as_str());
+        return result;
+    }
+
+    /// The dump of the value, knowing the objects and arrays being dumped around it, so one containing itself ends.
+    fn get_value_dump_inside(&self, containers: &mut Vec<usize>) -> String {
+        return match &self.constant {
+            Some(Constant::Object(members)) => get_object_dump(members, containers),
+            Some(Constant::Array(elements)) => get_array_dump(elements, containers),
+            Some(constant) => constant.get_dump(),
+            None => "".to_string()
+        };
+    }
+
+    pub fn assign(&mut self, other_variable: Rc<RefCell<Variable>>) {
+        // The only variable already borrowed mutably is this one, and assigning it to itself changes nothing.
+        let other_variable = match (*other_variable).try_borrow() {
+            Ok(other_variable) => other_variable,
+            Err(_) => return
+        };
+        self.constant = other_variable.constant.clone();
+    }
+}
+
+/// The members of an object between braces, `object {...}` standing for an object already being dumped.
+fn get_object_dump(members: &ObjectMembers, containers: &mut Vec<usize>) -> String {
+    let address = Rc::as_ptr(members) as usize;
+    if containers.contains(&address) {
+        return "object {...}".to_string();
+    }
+
+    containers.push(address);
+    let dumps: Vec<String> = members.deref().borrow().iter().map(|member| member.deref().borrow().get_dump_inside(containers)).collect();
+    containers.pop();
+    if dumps.is_empty() {
+        return "object { }".to_string();
+    }
+    return format!("object {{ {} }}", dumps.join(", "));
+}
+
+/// The elements of an array between brackets, `[...]` standing for an array already being dumped.
+pub fn get_array_dump(elements: &ArrayElements, containers: &mut Vec<usize>) -> String {
+    let address = Rc::as_ptr(elements) as usize;
+    if containers.contains(&address) {
+        return "[...]".to_string();
+    }
+
+    containers.push(address);
+    let dumps: Vec<String> = elements.deref().borrow().iter().map(|element| element.deref().borrow().get_value_dump_inside(containers)).collect();
+    containers.pop();
+    return format!("[{}]", dumps.join(", "));
+}
+
+/// The variables of a running block or function call, and the environment enclosing it.
+///
+/// Blocks get a new environment each time they run, so the parsed `Scope` of a block is never changed.
+pub struct Environment {
+    pub variables: Vec<Rc<RefCell<Variable>>>,
+    pub parent: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new(parent: Option<Rc<RefCell<Environment>>>) -> Environment {
+        return Environment {
+            variables: vec![],
+            parent,
+        };
+    }
+
+    /// The variable with the name in this environment or the closest enclosing one that has it.
+    pub fn get_variable(&self, name: &str) -> Option<Rc<RefCell<Variable>>> {
+        for variable in self.variables.iter() {
+            if variable.deref().borrow().name.as_ref().unwrap().eq(name) {
+                return Some(variable.clone());
+            }
+        }
+        return match &self.parent {
+            Some(parent) => parent.deref().borrow().get_variable(name),
+            None => None
+        };
+    }
+}
+
+pub enum VVA {
+    Variable(Rc<RefCell<Variable>>),
+    Value(Value),
+}
+
+impl VVA {
+    pub fn to_variable(self, environment: Rc<RefCell<Environment>>) -> Rc<RefCell<Variable>> {
+        match self {
+            VVA::Variable(var) => {
+                return var;
+            }
+            VVA::Value(val) => {
+                return construct_variable(val, environment);
+            }
+        }
+    }
+}
+
+/// How the execution of a statement ended.
+///
+/// Anything but `Normal` stops the statements of the current block, and is passed up until a loop or a function
+/// handles it.
+pub enum ControlFlow {
+    /// The statement completed, with the value of its expression if it has one.
+    Normal(Option<VVA>),
+    Break,
+    Continue,
+    Return(Rc<RefCell<Variable>>),
+}
+
+/// A SysCode function call an error went through.
+pub struct StackFrame {
+    /// The name the function was called by, `<anonymous>` if it wasn't called through a variable.
+    pub function: String,
+    pub call_site: Option<Span>,
+}
+
+impl Clone for StackFrame {
+    fn clone(&self) -> Self {
+        return StackFrame {
+            function: self.function.clone(),
+            call_site: self.call_site.clone(),
+        };
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match &self.call_site {
+            Some(call_site) => write!(f, "in '{}', called at {}", self.function, call_site),
+            None => write!(f, "in '{}'", self.function)
+        };
+    }
+}
+
+/// An error stopping the execution of a script, unless a `try` catches it.
+///
+/// The diagnostic locates where it happened, and the stack lists the calls that led there, the innermost first. The
+/// diagnostic is boxed to keep the results carrying the error small.
+pub struct RuntimeError {
+    pub diagnostic: Box<Diagnostic>,
+    pub stack: Vec<StackFrame>,
+    /// The value given to `throw`, errors found by the interpreter have none.
+    pub value: Option<Rc<RefCell<Variable>>>,
+}
+
+impl Clone for RuntimeError {
+    fn clone(&self) -> Self {
+        return RuntimeError {
+            diagnostic: self.diagnostic.clone(),
+            stack: self.stack.clone(),
+            value: self.value.clone(),
+        };
+    }
+}
+
+impl From<Diagnostic> for RuntimeError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        return RuntimeError {
+            diagnostic: Box::new(diagnostic),
+            stack: vec![],
+            value: None,
+        };
+    }
+}
+
+impl RuntimeError {
+    /// Records that the error left a call to the named function.
+    pub fn with_frame(mut self, function: &str, call_site: Option<&Span>) -> RuntimeError {
+        self.stack.push(StackFrame {
+            function: function.to_string(),
+            call_site: call_site.cloned(),
+        });
+        return self;
+    }
+
+    /// Renders the diagnostic followed by the stack trace, like:
+    ///
+    /// ```text
+    /// error: Division by zero.
+    ///  --> script.sys:2:12
+    ///   |
+    /// 2 |     return a / 0;
+    ///   |            ^^^^^
+    /// stack trace:
+    ///   in 'divide', called at script.sys:4:1
+    /// ```
+    pub fn render(&self) -> String {
+        let mut result = self.diagnostic.render();
+        if !self.stack.is_empty() {
+            result.push_str("\nstack trace:");
+        }
+        for line in self.get_frame_lines() {
+            result.push_str(format!("\n  {}", line).as_str());
         }
         return result;
     }
 
-    fn dump(&self) {
-        println!("{}", self.get_dump());
+    /// Where the error happened, then the calls it went through, one per line.
+    pub fn get_stack_trace(&self) -> String {
+        let mut lines: Vec<String> = vec![];
+        if let Some(span) = &self.diagnostic.span {
+            lines.push(format!("at {}", span));
+        }
+        lines.append(&mut self.get_frame_lines());
+        return lines.join("\n");
+    }
+
+    /// The frames one per line, a run of the same call, like a recursion, only being written once.
+    fn get_frame_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = vec![];
+        let mut index = 0;
+        while index < self.stack.len() {
+            let line = self.stack[index].to_string();
+            let mut count = 1;
+            while index + count < self.stack.len() && self.stack[index + count].to_string() == line {
+                count += 1;
+            }
+            lines.push(line);
+            if count > 1 {
+                lines.push(format!("... the same call {} more time{}", count - 1, if count == 2 { "" } else { "s" }));
+            }
+            index += count;
+        }
+        return lines;
+    }
+
+    /// What a `catch` gets : the thrown value, or an `Error` object for errors found by the interpreter.
+    ///
+    /// The `stack` member of an `Error` object is set to the stack trace of the error.
+    pub fn get_caught_value(&self) -> Rc<RefCell<Variable>> {
+        let value = match &self.value {
+            Some(value) => value.clone(),
+            None => construct_variable_from_error(self.diagnostic.message.clone())
+        };
+        let stack;
+        {
+            let variable = value.deref().borrow();
+            stack = if variable.get_member("message").is_some() { variable.get_member("stack") } else { None };
+        }
+        if let Some(stack) = stack {
+            stack.deref().borrow_mut().constant = Some(Constant::String(self.get_stack_trace()));
+        }
+        return value;
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.render());
+    }
+}
+
+/// Runs the statements of a scope in the given environment, stopping at the first one that doesn't end normally.
+pub fn execute_scope(scope: &Scope, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    for statement in scope.statements.iter() {
+        match execute_statement(statement, environment.clone())? {
+            ControlFlow::Normal(_) => {}
+            control_flow => {
+                return Ok(control_flow);
+            }
+        }
+    }
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+/// Runs a statement, each kind of statement having its own function so that nested calls only pay for the stack of the
+/// statements they actually run.
+pub fn execute_statement(statement: &Statement, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let span = statement.span.as_ref();
+    match &statement.kind {
+        StatementKind::Undefined => {
+            return Ok(ControlFlow::Normal(None));
+        }
+        StatementKind::Expression(expression) => {
+            return Ok(ControlFlow::Normal(Some(execute_expression(expression, environment)?)));
+        }
+        StatementKind::VariableDeclaration(expression) => {
+            return execute_variable_declaration(expression, span, environment);
+        },
+        StatementKind::ReturnStatement(expression) => {
+            // The value is copied, a variable captured by a function mustn't change the values it returned before.
+            let result;
+            {
+                result = copy_value(execute_expression(expression, environment.clone())?.to_variable(environment.clone()));
+            }
+            return Ok(ControlFlow::Return(result));
+        },
+        StatementKind::OperatorDeclaration(symbol, expression) => {
+            let function = execute_expression(expression, environment.clone())?.to_variable(environment.clone());
+            declare_variable(&environment, get_custom_operator_variable_name(symbol.as_str()), function);
+            return Ok(ControlFlow::Normal(None));
+        },
+        StatementKind::IfStatement(branches, else_scope) => {
+            return execute_if_statement(branches, else_scope, environment);
+        },
+        StatementKind::WhileStatement(condition, block) => {
+            return execute_while_statement(condition, block, environment);
+        },
+        StatementKind::ForStatement(initialization, condition, update, block) => {
+            return execute_for_statement(initialization, condition, update, block, environment);
+        },
+        StatementKind::ForOfStatement(name, collection, block) => {
+            return execute_for_of_statement(name, collection, block, span, environment);
+        },
+        StatementKind::BreakStatement => {
+            return Ok(ControlFlow::Break);
+        },
+        StatementKind::ContinueStatement => {
+            return Ok(ControlFlow::Continue);
+        }
+        StatementKind::DeleteStatement(expression) => {
+            return execute_delete_statement(expression, environment);
+        }
+        StatementKind::ThrowStatement(expression) => {
+            return execute_throw_statement(expression, span, environment);
+        }
+        StatementKind::TryStatement(block, catch, finally_block) => {
+            return execute_try_statement(block, catch, finally_block, environment);
+        }
+    }
+}
+
+fn execute_variable_declaration(expression: &Expression, span: Option<&Span>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    // Only `let name` and `let name = value` can declare a variable.
+    let mut declaration: Option<(&Expression, Option<&Expression>)> = None;
+    match expression.expression_type {
+        ExpressionType::Value => {
+            declaration = Some((expression, None));
+        }
+        ExpressionType::Operation => {
+            if let OperatorHook::Assign = expression.operator.as_ref().unwrap().hook {
+                declaration = Some((expression.left.as_ref().unwrap(), Some(expression.right.as_ref().unwrap())));
+            }
+        }
+        ExpressionType::Object | ExpressionType::Array | ExpressionType::Undefined => {}
+    }
+
+    let mut name: Option<String> = None;
+    if let Some((name_expression, _)) = declaration {
+        if let ExpressionType::Value = name_expression.expression_type {
+            if let ValueType::VariableName = name_expression.value.as_ref().unwrap().value_type {
+                name = name_expression.value.as_ref().unwrap().variable.clone();
+            }
+        }
+    }
+    if name.is_none() {
+        return Err(Diagnostic::error("Cannot create nameless variable.").at(span).into());
+    }
+
+    let value = match declaration.unwrap().1 {
+        Some(value_expression) => execute_expression(value_expression, environment.clone())?.to_variable(environment.clone()),
+        None => construct_variable(Value {
+            value_type: ValueType::Undefined,
+            constant: None,
+            variable: None,
+        }, environment.clone())
+    };
+    declare_variable(&environment, name.unwrap(), value);
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+fn execute_if_statement(branches: &Vec<(Expression, Rc<Scope>)>, else_scope: &Option<Rc<Scope>>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    for (condition, block) in branches.iter() {
+        let result = execute_expression(condition, environment.clone())?.to_variable(environment.clone());
+        let is_true;
+        {
+            is_true = result.deref().borrow().constant.as_ref().unwrap().as_boolean();
+        }
+        if is_true {
+            return execute_block(block, environment);
+        }
+    }
+
+    if else_scope.is_some() {
+        return execute_block(else_scope.as_ref().unwrap(), environment);
+    }
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+fn execute_while_statement(condition: &Expression, block: &Rc<Scope>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    loop {
+        let result = execute_expression(condition, environment.clone())?.to_variable(environment.clone());
+        let is_true;
+        {
+            is_true = result.deref().borrow().constant.as_ref().unwrap().as_boolean();
+        }
+        if !is_true {
+            break;
+        }
+
+        match execute_block(block, environment.clone())? {
+            ControlFlow::Break => {
+                break;
+            }
+            ControlFlow::Return(result) => {
+                return Ok(ControlFlow::Return(result));
+            }
+            ControlFlow::Normal(_) | ControlFlow::Continue => {}
+        }
+    }
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+fn execute_for_statement(initialization: &Option<Box<Statement>>, condition: &Option<Expression>, update: &Option<Expression>, block: &Rc<Scope>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let mut loop_environment = Rc::new(RefCell::new(Environment::new(Some(environment.clone()))));
+    if initialization.is_some() {
+        execute_statement(initialization.as_ref().unwrap(), loop_environment.clone())?;
+    }
+
+    let mut first_iteration = true;
+    loop {
+        // Every iteration gets its own copy of the variables declared by the header, the update then changes
+        // the copy, so what the previous iteration captured is left untouched.
+        let mut iteration_variables: Vec<Rc<RefCell<Variable>>> = vec![];
+        for variable in (*loop_environment).borrow().variables.iter() {
+            iteration_variables.push(Rc::new(RefCell::new(variable.deref().borrow().clone())));
+        }
+        loop_environment = Rc::new(RefCell::new(Environment {
+            variables: iteration_variables,
+            parent: Some(environment.clone())
+        }));
+
+        if !first_iteration && update.is_some() {
+            execute_expression(update.as_ref().unwrap(), loop_environment.clone())?;
+        }
+        first_iteration = false;
+
+        if condition.is_some() {
+            let result = execute_expression(condition.as_ref().unwrap(), loop_environment.clone())?.to_variable(loop_environment.clone());
+            let is_true;
+            {
+                is_true = result.deref().borrow().constant.as_ref().unwrap().as_boolean();
+            }
+            if !is_true {
+                break;
+            }
+        }
+
+        match execute_block(block, loop_environment.clone())? {
+            ControlFlow::Break => {
+                break;
+            }
+            ControlFlow::Return(result) => {
+                return Ok(ControlFlow::Return(result));
+            }
+            ControlFlow::Normal(_) | ControlFlow::Continue => {}
+        }
+    }
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+fn execute_for_of_statement(name: &str, collection: &Expression, block: &Rc<Scope>, span: Option<&Span>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let collection = execute_expression(collection, environment.clone())?.to_variable(environment.clone());
+    let constant;
+    {
+        constant = collection.deref().borrow().constant.clone().unwrap();
+    }
+
+    let items: Vec<Rc<RefCell<Variable>>> = match constant {
+        Constant::String(string) => {
+            string.chars().map(|c| construct_variable_from_string(c.to_string())).collect()
+        }
+        Constant::Tuple(tuple) => {
+            let mut items = vec![];
+            for e in (*tuple).borrow().expressions.iter() {
+                items.push(execute_expression(e, environment.clone())?.to_variable(environment.clone()));
+            }
+            items
+        }
+        // The elements as they are when the loop starts, pushing to the array doesn't make the loop longer.
+        Constant::Array(elements) => {
+            elements.deref().borrow().clone()
+        }
+        // Anything else has to follow the iterator protocol, `next()` returning `{ value, done }`.
+        _ => {
+            return execute_iterator_loop(name, &collection, block, span, environment);
+        }
+    };
+
+    for item in items {
+        match execute_for_of_iteration(name, item, block, environment.clone())? {
+            ControlFlow::Break => {
+                break;
+            }
+            ControlFlow::Return(result) => {
+                return Ok(ControlFlow::Return(result));
+            }
+            ControlFlow::Normal(_) | ControlFlow::Continue => {}
+        }
+    }
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+/// Runs a `for of` loop over an object following the iterator protocol, `next()` returning `{ value, done }`.
+fn execute_iterator_loop(name: &str, collection: &Rc<RefCell<Variable>>, block: &Rc<Scope>, span: Option<&Span>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let next = collection.deref().borrow().get_member("next");
+    let next = match next {
+        Some(next) => next.deref().borrow().constant.clone().unwrap(),
+        None => {
+            let type_name = collection.deref().borrow().constant.as_ref().unwrap().get_type_name();
+            return Err(Diagnostic::error(format!("Cannot iterate over {}.", type_name).as_str()).at(span).into());
+        }
+    };
+    let next = match next {
+        Constant::Function(f) => f,
+        _ => {
+            return Err(Diagnostic::error("Member 'next' is not a function.").at(span).into());
+        }
+    };
+
+    loop {
+        let result = call_function(&next, "next", vec![], environment.clone(), span)?;
+        if !matches!(result.deref().borrow().constant, Some(Constant::Object(_))) {
+            return Err(Diagnostic::error("Iterator result is not an object.").at(span).into());
+        }
+        let done = match result.deref().borrow().get_member("done") {
+            Some(done) => done.deref().borrow().constant.as_ref().unwrap().as_boolean(),
+            None => false
+        };
+        if done {
+            break;
+        }
+
+        let value = match result.deref().borrow().get_member("value") {
+            Some(value) => value,
+            None => construct_variable_from_undefined()
+        };
+        match execute_for_of_iteration(name, value, block, environment.clone())? {
+            ControlFlow::Break => {
+                break;
+            }
+            ControlFlow::Return(result) => {
+                return Ok(ControlFlow::Return(result));
+            }
+            ControlFlow::Normal(_) | ControlFlow::Continue => {}
+        }
+    }
+    return Ok(ControlFlow::Normal(None));
+}
+
+fn execute_delete_statement(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    match execute_member_target(expression, environment.clone())? {
+        Some((object, key)) => {
+            let name = get_member_name(&key)?;
+            if let Some(Constant::Object(members)) = &object.deref().borrow().constant {
+                members.deref().borrow_mut().retain(|member| !member.deref().borrow().name.as_ref().unwrap().eq(name.as_str()));
+            }
+        }
+        None => {
+            return Err(Diagnostic::error("Only members can be deleted.").at(expression.span.as_ref()).into());
+        }
+    }
+
+    return Ok(ControlFlow::Normal(None));
+}
+
+fn execute_throw_statement(expression: &Expression, span: Option<&Span>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let value = execute_expression(expression, environment.clone())?.to_variable(environment.clone());
+    let message;
+    {
+        let variable = value.deref().borrow();
+        message = match variable.get_member("message") {
+            Some(message) => message.deref().borrow().constant.as_ref().map_or("".to_string(), |constant| constant.get_dump()),
+            None => format!("Uncaught exception: {}.", variable.constant.as_ref().unwrap().get_dump())
+        };
+    }
+    return Err(RuntimeError {
+        diagnostic: Box::new(Diagnostic::error(message.as_str()).at(span)),
+        stack: vec![],
+        value: Some(value),
+    });
+}
+
+fn execute_try_statement(block: &Rc<Scope>, catch: &Option<(Option<String>, Rc<Scope>)>, finally_block: &Option<Rc<Scope>>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let mut result = execute_block(block, environment.clone());
+    if let Some((name, catch_block)) = catch {
+        if let Err(error) = result {
+            let catch_environment = Rc::new(RefCell::new(Environment::new(Some(environment.clone()))));
+            if let Some(name) = name {
+                declare_variable(&catch_environment, name.clone(), error.get_caught_value());
+            }
+            result = execute_block(catch_block, catch_environment);
+        }
+    }
+
+    // The finally block runs however the rest ended, and leaving it early replaces how that was.
+    if let Some(finally_block) = finally_block {
+        match execute_block(finally_block, environment)? {
+            ControlFlow::Normal(_) => {}
+            control_flow => {
+                return Ok(control_flow);
+            }
+        }
+    }
+    return result;
+}
+
+/// Declares a variable in the given environment, even if an enclosing environment has one with the same name.
+///
+/// Declaring a variable the environment already has assigns the value to it.
+fn declare_variable(environment: &Rc<RefCell<Environment>>, name: String, value: Rc<RefCell<Variable>>) -> Rc<RefCell<Variable>> {
+    for i in (**environment).borrow().variables.iter() {
+        if i.deref().borrow().name.as_ref().unwrap().eq(name.as_str()) {
+            i.deref().borrow_mut().assign(value);
+            return i.clone();
+        }
+    }
+
+    let variable = Rc::new(RefCell::new(Variable {
+        name: Some(name),
+        constant: None
+    }));
+    variable.deref().borrow_mut().assign(value);
+    (**environment).borrow_mut().variables.push(variable.clone());
+    return variable;
+}
+
+/// Runs the body of a `for of` loop with the loop variable bound to a copy of the item.
+fn execute_for_of_iteration(name: &str, item: Rc<RefCell<Variable>>, block: &Rc<Scope>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    let iteration_environment = Rc::new(RefCell::new(Environment::new(Some(environment))));
+    declare_variable(&iteration_environment, name.to_string(), item);
+    return execute_block(block, iteration_environment);
+}
+
+/// Runs a block of statements in a new child of the given environment.
+fn execute_block(block: &Rc<Scope>, environment: Rc<RefCell<Environment>>) -> Result<ControlFlow, RuntimeError> {
+    return execute_scope(block, Rc::new(RefCell::new(Environment::new(Some(environment)))));
+}
+
+/// The variable an `operator` declaration binds its function to, scripts can't name it since it isn't an identifier.
+fn get_custom_operator_variable_name(symbol: &str) -> String {
+    let mut name = "operator ".to_string();
+    name.push_str(symbol);
+    return name;
+}
+
+pub fn execute_expression(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<VVA, RuntimeError> {
+    // Expressions nest without calls, like a long chain of additions.
+    check_stack_size()?;
+    match expression.expression_type {
+        ExpressionType::Undefined => {
+            return Ok(VVA::Value(Value {
+                value_type: ValueType::Undefined,
+                constant: None,
+                variable: None,
+            }));
+        }
+        ExpressionType::Value => {
+            return Ok(execute_value(expression.value.as_ref().unwrap(), environment));
+        }
+        ExpressionType::Array => {
+            return execute_array(expression, environment);
+        }
+        ExpressionType::Object => {
+            return execute_object(expression, environment);
+        }
+        ExpressionType::Operation => {
+            // Errors found while applying an operator don't know where they happened, the operation locates them.
+            return execute_operation(expression, environment).map_err(|mut error| {
+                if error.diagnostic.span.is_none() {
+                    error.diagnostic.span = expression.span.clone();
+                }
+                error
+            });
+        }
+    }
+}
+
+fn execute_value(value: &Value, environment: Rc<RefCell<Environment>>) -> VVA {
+    match value.value_type {
+        ValueType::VariableName => {
+            return VVA::Value(value.clone());
+        },
+        _ => {
+            if let Some(Constant::Function(function)) = &value.constant {
+                return VVA::Variable(construct_variable_from_function(create_closure(function, environment)));
+            }
+            return VVA::Variable(construct_variable(value.clone(), environment));
+        }
+    }
+}
+
+fn execute_array(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<VVA, RuntimeError> {
+    let tuple = expression.value.as_ref().unwrap().constant.as_ref().unwrap().as_tuple()?;
+    let mut elements: Vec<Rc<RefCell<Variable>>> = vec![];
+    for e in (*tuple).borrow().expressions.iter() {
+        elements.push(copy_value(execute_expression(e, environment.clone())?.to_variable(environment.clone())));
+    }
+    return Ok(VVA::Variable(construct_variable_from_array(elements)));
+}
+
+fn execute_object(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<VVA, RuntimeError> {
+    let object = construct_variable_from_object(vec![]);
+    let tuple = expression.value.as_ref().unwrap().constant.as_ref().unwrap().as_tuple()?;
+    for member in (*tuple).borrow().expressions.iter() {
+        let name = member.left.as_ref().unwrap().value.as_ref().unwrap().variable.clone().unwrap();
+        let value = execute_expression(member.right.as_ref().unwrap(), environment.clone())?.to_variable(environment.clone());
+        assign_member(&object, name.as_str(), value)?;
+    }
+    return Ok(VVA::Variable(object));
+}
+
+/// The function a `function` expression evaluates to, capturing the environment it is evaluated in.
+///
+/// Each evaluation creates a new function, so functions created by different calls keep their own variables.
+///
+/// The function keeps the environment alive, and the environment often holds the function, like in
+/// `let f = function() { ... };` or when a call returns a function it declared. The collector frees such cycles once
+/// nothing else uses them.
+fn create_closure(function: &Rc<RefCell<dyn Callable>>, environment: Rc<RefCell<Environment>>) -> Rc<RefCell<dyn Callable>> {
+    collector::add_environment(&environment);
+    let function = function.deref().borrow();
+    return Rc::new(RefCell::new(Function {
+        args: function.get_args().cloned().unwrap_or_default(),
+        scope: function.get_scope().unwrap(),
+        closure: Some(environment)
+    }));
+}
+
+fn execute_operation(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<VVA, RuntimeError> {
+    let operator = expression.operator.as_ref().unwrap();
+    if expression.left.is_none() {
+        return execute_prefix_operation(expression, environment);
+    }
+    if expression.right.is_none() {
+        return Err(Diagnostic::error(format!("Operator '{}' cannot be applied to a single operand.", operator.symbol).as_str()).into());
+    }
+    // Assigning to a member creates it if it is missing, so the member isn't looked up like other operands.
+    if let OperatorHook::Assign = operator.hook {
+        if let Some(result) = execute_member_assignment(expression, environment.clone())? {
+            return Ok(VVA::Variable(result));
+        }
+    }
+    let left_value = execute_expression(expression.left.as_ref().unwrap(), environment.clone())?.to_variable(environment.clone());
+
+    let right_value = execute_expression(expression.right.as_ref().unwrap(), environment.clone())?;
+    if let (VVA::Value(value), OperatorHook::Member) = (&right_value, operator.hook) {
+        if let ValueType::VariableName = value.value_type {
+            return Ok(VVA::Variable(Variable::apply_operator_right_vn(left_value, value.variable.as_ref().unwrap(), operator, environment)?));
+        }
+    }
+    let right = right_value.to_variable(environment.clone());
+    return Ok(VVA::Variable(Variable::apply_operator_right(left_value, right, operator, environment, expression)?));
+}
+
+fn execute_prefix_operation(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<VVA, RuntimeError> {
+    let operand = execute_expression(expression.right.as_ref().unwrap(), environment.clone())?.to_variable(environment);
+    let result;
+    {
+        result = apply_prefix_operator(expression.operator.as_ref().unwrap(), (*operand).borrow().constant.as_ref().unwrap())?;
+    }
+    return Ok(VVA::Variable(Rc::new(RefCell::new(Variable {
+        name: None,
+        constant: Some(result)
+    }))));
+}
+
+/// Runs `object.name = value` or `object[key] = value`, or returns `None` for other assignments.
+fn execute_member_assignment(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<Option<Rc<RefCell<Variable>>>, RuntimeError> {
+    let (object, key) = match execute_member_target(expression.left.as_ref().unwrap(), environment.clone())? {
+        Some(target) => target,
+        None => return Ok(None)
+    };
+    let value = execute_expression(expression.right.as_ref().unwrap(), environment.clone())?.to_variable(environment);
+    if let Some(elements) = get_indexed_elements(&object, &key) {
+        return Ok(Some(assign_element(&elements, &key, value)?));
+    }
+    return Ok(Some(assign_member(&object, get_member_name(&key)?.as_str(), value)?));
+}
+
+/// An object or an array, and the member name or the index of one of its members or elements, which may not exist yet.
+type MemberTarget = (Rc<RefCell<Variable>>, Constant);
+
+/// The object and the key of an `object.name` or `object[key]` expression, or `None` for other expressions.
+fn execute_member_target(expression: &Expression, environment: Rc<RefCell<Environment>>) -> Result<Option<MemberTarget>, RuntimeError> {
+    if expression.left.is_none() || expression.right.is_none() {
+        return Ok(None);
+    }
+    let right = expression.right.as_ref().unwrap();
+    match expression.operator.as_ref().unwrap().hook {
+        OperatorHook::Member => {
+            let name = match &right.value {
+                Some(value) if matches!(value.value_type, ValueType::VariableName) => value.variable.clone().unwrap(),
+                _ => return Ok(None)
+            };
+            let object = execute_expression(expression.left.as_ref().unwrap(), environment.clone())?.to_variable(environment.clone());
+            return Ok(Some((object, Constant::String(name))));
+        }
+        OperatorHook::Index => {
+            let object = execute_expression(expression.left.as_ref().unwrap(), environment.clone())?.to_variable(environment.clone());
+            let key = execute_expression(right, environment.clone())?.to_variable(environment.clone());
+            let key = key.deref().borrow().constant.clone().unwrap();
+            return Ok(Some((object, key)));
+        }
+        _ => {
+            return Ok(None);
+        }
+    }
+}
+
+/// The member name a value between brackets stands for.
+fn get_member_name(key: &Constant) -> Result<String, RuntimeError> {
+    match key {
+        Constant::String(name) => {
+            return Ok(name.clone());
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Member names are strings, found {}.", key.get_type_name()).as_str()).into());
+        }
+    }
+}
+
+/// The elements of the array when the key indexes them, `None` when the key names a member, like `length`.
+fn get_indexed_elements(variable: &Rc<RefCell<Variable>>, key: &Constant) -> Option<ArrayElements> {
+    if let Constant::String(_) = key {
+        return None;
+    }
+    return match &variable.deref().borrow().constant {
+        Some(Constant::Array(elements)) => Some(elements.clone()),
+        _ => None
+    };
+}
+
+/// The index a value between brackets stands for in an array.
+fn get_array_index(key: &Constant) -> Result<usize, RuntimeError> {
+    match key {
+        Constant::Integer(i) if *i < 0 => {
+            return Err(Diagnostic::error(format!("Array indices cannot be negative, found {}.", i).as_str()).into());
+        }
+        Constant::Integer(i) => {
+            return Ok(*i as usize);
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Array indices are integers, found {}.", key.get_type_name()).as_str()).into());
+        }
+    }
+}
+
+/// The element at the index, or undefined past the end of the array.
+fn get_element(elements: &ArrayElements, key: &Constant) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    let index = get_array_index(key)?;
+    return match elements.deref().borrow().get(index) {
+        Some(element) => Ok(element.clone()),
+        None => Ok(construct_variable_from_undefined())
+    };
+}
+
+/// How many undefined elements assigning past the end of an array can add before the assigned one.
+const MAX_ARRAY_GAP: usize = 65536;
+
+/// Assigns the value to the element at the index, filling the array with undefined up to it if it is past the end.
+fn assign_element(elements: &ArrayElements, key: &Constant, value: Rc<RefCell<Variable>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    let index = get_array_index(key)?;
+    let element = elements.deref().borrow().get(index).cloned();
+    if let Some(element) = element {
+        element.deref().borrow_mut().assign(value);
+        return Ok(element);
+    }
+
+    // A huge index would fill all the memory with undefined elements.
+    let length = elements.deref().borrow().len();
+    if index - length > MAX_ARRAY_GAP {
+        return Err(Diagnostic::error(format!("Cannot assign element {} of an array of length {}, at most {} elements past the end.", index, length, MAX_ARRAY_GAP).as_str()).into());
+    }
+
+    let element = copy_value(value);
+    let mut elements = elements.deref().borrow_mut();
+    while elements.len() < index {
+        elements.push(construct_variable_from_undefined());
+    }
+    elements.push(element.clone());
+    return Ok(element);
+}
+
+/// A new variable holding the value, for storing it in an array or returning it.
+fn copy_value(value: Rc<RefCell<Variable>>) -> Rc<RefCell<Variable>> {
+    let copy = construct_variable_from_undefined();
+    copy.deref().borrow_mut().assign(value);
+    return copy;
+}
+
+/// Assigns the value to a member of the object, adding the member if the object doesn't have it yet.
+///
+/// Only objects can get new members.
+fn assign_member(object: &Rc<RefCell<Variable>>, name: &str, value: Rc<RefCell<Variable>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    let member = object.deref().borrow().get_member(name);
+    if let Some(member) = member {
+        (*member).borrow_mut().assign(value);
+        return Ok(member);
+    }
+
+    let members = match &object.deref().borrow().constant {
+        Some(Constant::Object(members)) => members.clone(),
+        constant => {
+            let type_name = constant.as_ref().map_or("undefined", |constant| constant.get_type_name());
+            return Err(Diagnostic::error(format!("Cannot add member '{}' to {}.", name, type_name).as_str()).into());
+        }
+    };
+    let member = Rc::new(RefCell::new(Variable {
+        name: Some(name.to_string()),
+        constant: None
+    }));
+    (*member).borrow_mut().assign(value);
+    members.deref().borrow_mut().push(member.clone());
+    return Ok(member);
+}
+
+trait Evaluable {
+    /// `operation` is the expression the operator comes from, it locates the calls the operator makes.
+    fn apply_operator_right(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &Operator, environment: Rc<RefCell<Environment>>, operation: &Expression) -> Result<Rc<RefCell<Variable>>, RuntimeError>;
+    fn apply_operator_right_vn(var_ref_cell: Rc<RefCell<Variable>>, right: &String, operator: &Operator, environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError>;
+}
+
+impl Evaluable for Variable {
+    fn apply_operator_right(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &Operator, environment: Rc<RefCell<Environment>>, operation: &Expression) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        if let Some(result) = apply_overload(var_ref_cell.clone(), right.clone(), operator, environment.clone(), operation)? {
+            return Ok(result);
+        }
+
+        // Each kind of operator has its own function, so that the calls made by one only pay for its own stack.
+        match operator.hook {
+            OperatorHook::Assign => {
+                (*var_ref_cell.clone()).borrow_mut().assign(right);
+                return Ok(var_ref_cell);
+            },
+            OperatorHook::Call => {
+                return apply_call(var_ref_cell, right, environment, operation);
+            }
+            OperatorHook::Index => {
+                let key = (*right).borrow().constant.clone().unwrap();
+                if let Some(elements) = get_indexed_elements(&var_ref_cell, &key) {
+                    return get_element(&elements, &key);
+                }
+                return Variable::apply_operator_right_vn(var_ref_cell, &get_member_name(&key)?, operator, environment);
+            }
+            OperatorHook::Add | OperatorHook::Subtract | OperatorHook::Multiply | OperatorHook::Divide
+            | OperatorHook::AddAssign | OperatorHook::SubtractAssign | OperatorHook::MultiplyAssign | OperatorHook::DivideAssign => {
+                return apply_arithmetic_operator(var_ref_cell, right, operator);
+            }
+            OperatorHook::Equal | OperatorHook::NotEqual | OperatorHook::Less | OperatorHook::Greater
+            | OperatorHook::LessEqual | OperatorHook::GreaterEqual => {
+                return apply_comparison(var_ref_cell, right, operator);
+            }
+            OperatorHook::Custom => {
+                return apply_custom_operator(var_ref_cell, right, operator, environment, operation);
+            }
+            _ => {
+                return Err(Diagnostic::error(format!("Unknown operator '{}'.", operator.symbol).as_str()).into());
+            }
+        }
+    }
+
+    fn apply_operator_right_vn(var_ref_cell: Rc<RefCell<Variable>>, right: &String, operator: &Operator, _environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        match operator.hook {
+            OperatorHook::Member | OperatorHook::Index => {
+                let deref = var_ref_cell.clone();
+
+                if let Some(Constant::Array(elements)) = &deref.deref().borrow().constant {
+                    let method: Option<Rc<RefCell<dyn Callable>>> = match right.as_str() {
+                        "length" => return Ok(construct_variable_from_integer(elements.deref().borrow().len() as i64)),
+                        "push" => Some(Rc::new(RefCell::new(ArrayPushFunction { elements: elements.clone() }))),
+                        "pop" => Some(Rc::new(RefCell::new(ArrayPopFunction { elements: elements.clone() }))),
+                        "slice" => Some(Rc::new(RefCell::new(ArraySliceFunction { elements: elements.clone() }))),
+                        _ => None
+                    };
+                    if let Some(method) = method {
+                        return Ok(construct_variable_from_function(method));
+                    }
+                }
+
+                if let Some(member) = deref.deref().borrow().get_member(right.as_str()) {
+                    return Ok(member);
+                }
+
+                let type_name = match &deref.deref().borrow().constant {
+                    Some(constant) => constant.get_type_name(),
+                    None => "undefined"
+                };
+                return Err(Diagnostic::error(format!("No member '{}' in {}.", right, type_name).as_str()).into());
+            }
+            _ => {
+                return Err(Diagnostic::error(format!("Unknown operator for variable names '{}'.", operator.symbol).as_str()).into());
+            }
+        }
+    }
+}
+
+/// Calls the overload of the operator the left operand defines, if it has one.
+///
+/// Without `__ne__`, `!=` is the opposite of `__eq__`.
+fn apply_overload(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &Operator, environment: Rc<RefCell<Environment>>, operation: &Expression) -> Result<Option<Rc<RefCell<Variable>>>, RuntimeError> {
+    let overload_name = match operator.hook.get_overload_name() {
+        Some(overload_name) => overload_name,
+        None => return Ok(None)
+    };
+    if let Some(result) = Variable::call_overload(var_ref_cell.clone(), right.clone(), overload_name, environment.clone(), operation.span.as_ref())? {
+        match operator.hook {
+            OperatorHook::AddAssign | OperatorHook::SubtractAssign | OperatorHook::MultiplyAssign | OperatorHook::DivideAssign => {
+                (*var_ref_cell).borrow_mut().assign(result);
+                return Ok(Some(var_ref_cell));
+            }
+            _ => {
+                return Ok(Some(result));
+            }
+        }
+    }
+    if let OperatorHook::NotEqual = operator.hook {
+        if let Some(result) = Variable::call_overload(var_ref_cell, right, "__eq__", environment, operation.span.as_ref())? {
+            let is_equal = (*result).borrow().constant.as_ref().is_some_and(|constant| constant.as_boolean());
+            return Ok(Some(construct_variable_from_boolean(!is_equal)));
+        }
+    }
+    return Ok(None);
+}
+
+/// Calls the function on the left with the arguments of the tuple on the right.
+fn apply_call(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, environment: Rc<RefCell<Environment>>, operation: &Expression) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    let constant;
+    {
+        constant = var_ref_cell.as_ref().borrow().constant.clone().unwrap();
+    }
+    match constant {
+        Constant::Function(f) => {
+            let tuple = (*right).borrow().constant.as_ref().unwrap().as_tuple()?;
+            let mut args: Vec<Rc<RefCell<Variable>>> = vec![];
+            for e in (*tuple).borrow().expressions.iter() {
+                args.push(execute_expression(e, environment.clone())?.to_variable(environment.clone()));
+            }
+            let name = get_callee_name(operation.left.as_ref().unwrap());
+            return call_function(&f, name.as_str(), args, environment, operation.span.as_ref());
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Cannot call {}.", constant.get_type_name()).as_str()).into());
+        }
+    };
+}
+
+fn apply_arithmetic_operator(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &Operator) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    match operator.hook {
+        OperatorHook::AddAssign | OperatorHook::SubtractAssign | OperatorHook::MultiplyAssign | OperatorHook::DivideAssign => {
+            let arithmetic_hook = match operator.hook {
+                OperatorHook::AddAssign => OperatorHook::Add,
+                OperatorHook::SubtractAssign => OperatorHook::Subtract,
+                OperatorHook::MultiplyAssign => OperatorHook::Multiply,
+                _ => OperatorHook::Divide
+            };
+            let result;
+            {
+                result = apply_arithmetic(operator, arithmetic_hook, (*var_ref_cell).borrow().constant.as_ref().unwrap(), (*right).borrow().constant.as_ref().unwrap())?;
+            }
+            (*var_ref_cell).borrow_mut().constant = Some(result);
+            return Ok(var_ref_cell);
+        }
+        _ => {
+            let result;
+            {
+                result = apply_arithmetic(operator, operator.hook, (*var_ref_cell).borrow().constant.as_ref().unwrap(), (*right).borrow().constant.as_ref().unwrap())?;
+            }
+            return Ok(Rc::new(RefCell::new(Variable {
+                name: None,
+                constant: Some(result)
+            })));
+        }
+    }
+}
+
+fn apply_comparison(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &Operator) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    match operator.hook {
+        OperatorHook::Equal | OperatorHook::NotEqual => {
+            let equal;
+            {
+                equal = are_variables_equal(&var_ref_cell.deref().borrow(), &right.deref().borrow());
+            }
+            if let OperatorHook::NotEqual = operator.hook {
+                return Ok(construct_variable_from_boolean(!equal));
+            }
+            return Ok(construct_variable_from_boolean(equal));
+        }
+        _ => {
+            let ordering;
+            {
+                ordering = compare_constants((*var_ref_cell).borrow().constant.as_ref().unwrap(), (*right).borrow().constant.as_ref().unwrap())?;
+            }
+            let result = match ordering {
+                Some(ordering) => match operator.hook {
+                    OperatorHook::Less => ordering == Ordering::Less,
+                    OperatorHook::Greater => ordering == Ordering::Greater,
+                    OperatorHook::LessEqual => ordering != Ordering::Greater,
+                    _ => ordering != Ordering::Less
+                },
+                None => false
+            };
+            return Ok(construct_variable_from_boolean(result));
+        }
+    }
+}
+
+/// Calls the function an `operator` declaration bound to the symbol.
+fn apply_custom_operator(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &Operator, environment: Rc<RefCell<Environment>>, operation: &Expression) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    // The declaration is a variable of the scope it runs in. The environment of the expression has the scopes around
+    // it in the source as parents, the closure of a function being the scope that created it, so this finds the
+    // closest declaration around the expression, never one made by a caller.
+    let name = get_custom_operator_variable_name(operator.symbol.as_str());
+    let implementation = environment.deref().borrow().get_variable(name.as_str());
+    let constant = implementation.and_then(|implementation| implementation.deref().borrow().constant.clone());
+    match constant {
+        Some(Constant::Function(f)) => {
+            return call_function(&f, name.as_str(), vec![var_ref_cell, right], environment, operation.span.as_ref());
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Operator '{}' has no implementation in this scope.", operator.symbol).as_str()).into());
+        }
+    }
+}
+
+impl Variable {
+    /// The member of an object with the name, values other than objects having none.
+    pub fn get_member(&self, name: &str) -> Option<Rc<RefCell<Variable>>> {
+        if let Some(Constant::Object(members)) = &self.constant {
+            for member in members.deref().borrow().iter() {
+                if member.deref().borrow().name.as_ref().unwrap().eq(name) {
+                    return Some(member.clone());
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Calls the member overloading an operator with both operands, if the left operand defines it.
+    fn call_overload(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, overload_name: &str, environment: Rc<RefCell<Environment>>, call_site: Option<&Span>) -> Result<Option<Rc<RefCell<Variable>>>, RuntimeError> {
+        let overload = (*var_ref_cell).borrow().get_member(overload_name);
+        let constant = match overload {
+            Some(overload) => overload.deref().borrow().constant.clone().unwrap(),
+            None => return Ok(None)
+        };
+        match constant {
+            Constant::Function(f) => {
+                return Ok(Some(call_function(&f, overload_name, vec![var_ref_cell, right], environment, call_site)?));
+            }
+            _ => {
+                return Err(Diagnostic::error(format!("Member '{}' is not a function.", overload_name).as_str()).into());
+            }
+        }
     }
 }
 
-impl Variable {
-    fn assign(&mut self, other_variable: Rc<RefCell<Variable>>) {
-        self.constant = (*other_variable).borrow().constant.clone();
-        self.members = (*other_variable).borrow().members.clone();
-    }
+/// How many calls of SysCode functions can be nested unless the interpreter is configured otherwise.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// How many bytes of the native stack a running script can use when the size of the stack of the thread is unknown,
+/// unless the interpreter is configured otherwise.
+///
+/// Half of the 2 MiB that Rust gives to spawned threads, the rest being left to the frames of the last call or
+/// expression and to those of the program running the interpreter.
+pub const DEFAULT_MAX_STACK_SIZE: usize = 1024 * 1024;
+
+/// How many bytes at the end of the stack of the thread scripts leave alone when their limit comes from the size of
+/// that stack, for the frames running between two checks of the stack and for the program running the interpreter.
+pub const STACK_RESERVE: usize = 256 * 1024;
+
+thread_local! {
+    /// The SysCode functions running on this thread, and how many of them can be nested.
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+    static MAX_CALL_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_CALL_DEPTH) };
+    /// Where the stack was when the outermost script started running, and how far from it calls can go.
+    static STACK_START: Cell<usize> = const { Cell::new(0) };
+    static STACK_SIZE: Cell<usize> = const { Cell::new(DEFAULT_MAX_STACK_SIZE) };
+    /// The limit set by the interpreter, the stack of the thread deciding it when there is none.
+    static MAX_STACK_SIZE: Cell<Option<usize>> = const { Cell::new(None) };
+    /// The lowest address of the stack of this thread, if the platform tells it.
+    static STACK_END: Option<usize> = get_thread_stack_end();
 }
 
-pub enum VVA {
-    Variable(Rc<RefCell<Variable>>),
-    Value(Value),
+/// Sets how many calls of SysCode functions can be nested on this thread before a call fails with a stack overflow.
+pub fn set_max_call_depth(depth: usize) {
+    MAX_CALL_DEPTH.with(|max_depth| max_depth.set(depth));
 }
 
-impl VVA {
-    fn to_variable(self, scope: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>> {
-        match self {
-            VVA::Variable(var) => {
-                return var;
-            }
-            VVA::Value(val) => {
-                return construct_variable(val, scope);
-            }
-        }
-    }
+/// Sets how many bytes of the native stack a script can use on this thread before a call or an expression fails with a
+/// stack overflow.
+///
+/// Without a size, scripts can use the whole stack of the thread but `STACK_RESERVE`, or `DEFAULT_MAX_STACK_SIZE`
+/// bytes on platforms where its size is unknown.
+pub fn set_max_stack_size(size: Option<usize>) {
+    MAX_STACK_SIZE.with(|max_size| max_size.set(size));
 }
 
-pub fn execute_scope(scope: Rc<RefCell<Scope>>) -> Option<VVA> {
-    let statementslen = (*scope).borrow().statements.len();
-    for i in 0..statementslen {
-        let statement;
-        {
-            let s = scope.clone();
-            let s1 = (*s).borrow();
-            statement = s1.statements[i].clone();
-        }
-        execute_statement(statement.borrow(), scope.clone());
-        if (*scope).borrow().return_value.is_some() {
-            return Some(VVA::Variable((*scope).borrow().return_value.as_ref().unwrap().clone()));
-        }
+/// Remembers the current position of the stack as the start of the script or of the parse, unless a script is already
+/// running on this thread, a native function running another one.
+pub fn mark_stack_start() {
+    if CALL_DEPTH.with(|depth| depth.get()) != 0 {
+        return;
     }
 
-    return None;
+    let start = get_stack_position();
+    let size = match MAX_STACK_SIZE.with(|max_size| max_size.get()) {
+        Some(size) => size,
+        None => match STACK_END.with(|end| *end) {
+            Some(end) => start.saturating_sub(end).saturating_sub(STACK_RESERVE),
+            None => DEFAULT_MAX_STACK_SIZE
+        }
+    };
+    STACK_START.with(|stack_start| stack_start.set(start));
+    STACK_SIZE.with(|stack_size| stack_size.set(size));
 }
 
-pub fn execute_statement(statement: &Statement, scope: Rc<RefCell<Scope>>) -> Option<VVA> {
-    match statement {
-        Statement::Undefined => {
+/// The lowest address of the stack of the current thread, stacks growing down on every platform it is known on.
+#[cfg(target_os = "linux")]
+fn get_thread_stack_end() -> Option<usize> {
+    /// Room for a `pthread_attr_t`, 56 or 64 bytes depending on the target.
+    #[repr(C)]
+    struct ThreadAttributes([u64; 16]);
+
+    extern "C" {
+        fn pthread_self() -> usize;
+        fn pthread_getattr_np(thread: usize, attributes: *mut ThreadAttributes) -> i32;
+        fn pthread_attr_getstack(attributes: *const ThreadAttributes, address: *mut usize, size: *mut usize) -> i32;
+        fn pthread_attr_destroy(attributes: *mut ThreadAttributes) -> i32;
+    }
+
+    let mut attributes = ThreadAttributes([0; 16]);
+    let mut address = 0;
+    let mut size = 0;
+    // SAFETY: the attributes are initialized by `pthread_getattr_np` before being read, and destroyed once.
+    unsafe {
+        if pthread_getattr_np(pthread_self(), &mut attributes) != 0 {
             return None;
         }
-        Statement::Expression(expression) => {
-            return Some(execute_expression(expression, scope));
+        let result = pthread_attr_getstack(&attributes, &mut address, &mut size);
+        pthread_attr_destroy(&mut attributes);
+        if result != 0 || address == 0 {
+            return None;
         }
-        Statement::VariableDeclaration(expression) => {
-            let result;
-            {
-                result = execute_expression(expression, scope.clone()).to_variable(scope.clone());
-            }
-            if (*result).borrow().name.is_some() {
-                {
-                    for i in (*scope).borrow().accessible_variables.iter() {
-                        if i.deref().borrow().name.as_ref().unwrap().eq((*result).borrow().name.as_ref().unwrap().as_str()) {
-                            i.deref().borrow_mut().assign(result.clone());
-                            return None;
-                        }
-                    }
-                }
+    }
+    return Some(address);
+}
 
-                (*scope.clone()).borrow_mut().accessible_variables.push(result.clone());
-            } else {
-                println!("Error: Cannot create nameless variable.");
-                panic!();
-            }
-        },
-        Statement::ReturnStatement(expression) => {
-            let result;
-            {
-                result = execute_expression(expression, scope.clone()).to_variable(scope.clone());
-            }
-            (*scope.clone()).borrow_mut().return_value = Some(result.clone());
-        }
+#[cfg(target_os = "macos")]
+fn get_thread_stack_end() -> Option<usize> {
+    extern "C" {
+        fn pthread_self() -> usize;
+        fn pthread_get_stackaddr_np(thread: usize) -> usize;
+        fn pthread_get_stacksize_np(thread: usize) -> usize;
+    }
+
+    // SAFETY: these only read the description of the current thread, `pthread_get_stackaddr_np` giving the top of its
+    // stack.
+    let (top, size) = unsafe { (pthread_get_stackaddr_np(pthread_self()), pthread_get_stacksize_np(pthread_self())) };
+    if top == 0 || size == 0 {
+        return None;
     }
+    return Some(top - size);
+}
 
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn get_thread_stack_end() -> Option<usize> {
     return None;
 }
 
-pub fn execute_expression(expression: &Expression, scope: Rc<RefCell<Scope>>) -> VVA {
-    match expression.expression_type {
-        ExpressionType::Undefined => {
-            return VVA::Value(Value {
-                value_type: ValueType::Undefined,
-                constant: None,
-                variable: None,
-            });
+/// Whether the running script, or the source being parsed, uses more of the stack than allowed since the start was
+/// marked.
+pub fn is_stack_exhausted() -> bool {
+    let stack_start = STACK_START.with(|start| start.get());
+    let stack_size = STACK_SIZE.with(|size| size.get());
+    return stack_start != 0 && stack_start.abs_diff(get_stack_position()) > stack_size;
+}
+
+/// Fails if the running script uses more of the stack than allowed, before the thread runs out of it.
+fn check_stack_size() -> Result<(), RuntimeError> {
+    if is_stack_exhausted() {
+        let stack_size = STACK_SIZE.with(|size| size.get());
+        return Err(Diagnostic::error(format!("Stack overflow, more than {} bytes of stack used.", stack_size).as_str()).into());
+    }
+    return Ok(());
+}
+
+/// The address of a local variable, close to the top of the stack.
+#[inline(never)]
+fn get_stack_position() -> usize {
+    let marker = 0u8;
+    return &marker as *const u8 as usize;
+}
+
+/// Counts a running SysCode function until it is dropped, so the count stays right when a call fails or panics.
+struct CallDepthGuard;
+
+impl CallDepthGuard {
+    fn enter() -> Result<CallDepthGuard, RuntimeError> {
+        let depth = CALL_DEPTH.with(|depth| depth.get());
+        let max_depth = MAX_CALL_DEPTH.with(|max_depth| max_depth.get());
+        if depth >= max_depth {
+            return Err(Diagnostic::error(format!("Stack overflow, more than {} nested calls.", max_depth).as_str()).into());
         }
+
+        // The frames of a call are big, a debug build using several kilobytes for each, so the thread could run out of
+        // stack before the depth limit without this.
+        check_stack_size()?;
+        CALL_DEPTH.with(|call_depth| call_depth.set(depth + 1));
+        return Ok(CallDepthGuard);
+    }
+}
+
+impl Drop for CallDepthGuard {
+    fn drop(&mut self) {
+        CALL_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Calls a function, adding the call to the stack of the errors leaving a SysCode function.
+///
+/// The function is only borrowed immutably, so it can call itself.
+fn call_function(function: &Rc<RefCell<dyn Callable>>, name: &str, args: Vec<Rc<RefCell<Variable>>>, environment: Rc<RefCell<Environment>>, call_site: Option<&Span>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+    let is_native = function.deref().borrow().get_scope().is_none();
+    if is_native {
+        return function.deref().borrow().call(args, environment);
+    }
+
+    let _guard = CallDepthGuard::enter()?;
+    let result = function.deref().borrow().call(args, environment);
+    return result.map_err(|error| error.with_frame(name, call_site));
+}
+
+/// The name a function is called by, for stack traces : `f` for `f()` and `object.f` for `object.f()`.
+fn get_callee_name(callee: &Expression) -> String {
+    match callee.expression_type {
         ExpressionType::Value => {
-            match expression.value.as_ref().unwrap().value_type {
-                ValueType::VariableName => {
-                    return VVA::Value(expression.value.as_ref().unwrap().clone());
-                },
-                _ => {
-                    return VVA::Variable(construct_variable(expression.value.as_ref().unwrap().clone(), scope));
-                }
+            if let Some(name) = &callee.value.as_ref().unwrap().variable {
+                return name.clone();
             }
         }
         ExpressionType::Operation => {
-            let left_value = execute_expression(expression.left.as_ref().unwrap(), scope.clone()).to_variable(scope.clone());
-
-            let right_value = execute_expression(expression.right.as_ref().unwrap(), scope.clone());
-            match right_value {
-                VVA::Variable(variable) => {
-                    let result = Variable::apply_operator_right(
-                        left_value,
-                        variable.clone(),
-                        expression.operator.as_ref().unwrap(),
-                        scope.clone(),
-                    );
-                    return VVA::Variable(result);
-                }
-                VVA::Value(value) => {
-                    match value.value_type {
-                        ValueType::VariableName => {
-                            if expression.operator.as_ref().unwrap().eq(".") {
-                                let result = Variable::apply_operator_right_vn(
-                                    left_value,
-                                    value.variable.as_ref().unwrap(),
-                                    expression.operator.as_ref().unwrap(),
-                                    scope.clone(),
-                                );
-                                return VVA::Variable(result);
-                            } else {
-                                let result = Variable::apply_operator_right(
-                                    left_value,
-                                    construct_variable(value, scope.clone()),
-                                    expression.operator.as_ref().unwrap(),
-                                    scope.clone(),
-                                );
-                                return VVA::Variable(result);
-                            }
-                        }
-                        _ => {
-                            let result = Variable::apply_operator_right(
-                                left_value,
-                                construct_variable(value, scope.clone()),
-                                expression.operator.as_ref().unwrap(),
-                                scope.clone(),
-                            );
-                            return VVA::Variable(result);
-                        }
-                    };
-                }
+            if let OperatorHook::Member = callee.operator.as_ref().unwrap().hook {
+                let object = get_callee_name(callee.left.as_ref().unwrap());
+                let member = get_callee_name(callee.right.as_ref().unwrap());
+                return format!("{}.{}", object, member);
             }
         }
+        ExpressionType::Object | ExpressionType::Array | ExpressionType::Undefined => {}
     }
+    return "<anonymous>".to_string();
 }
 
-trait Evaluable {
-    fn apply_operator_right(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &String, accessible_variables: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>>;
-    fn apply_operator_right_vn(var_ref_cell: Rc<RefCell<Variable>>, right: &String, operator: &String, accessible_variables: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>>;
-}
-
-impl Evaluable for Variable {
-    fn apply_operator_right(var_ref_cell: Rc<RefCell<Variable>>, right: Rc<RefCell<Variable>>, operator: &String, scope: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>> {
-        match operator.as_str() {
-            "=" => {
-                (*var_ref_cell.clone()).borrow_mut().assign(right);
-                return var_ref_cell;
-            },
-            "(" => {
-                let constant;
-                {
-                    constant = var_ref_cell.as_ref().borrow().constant.clone().unwrap();
-                }
-                match constant {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Function(f) => {
-                        return f.deref().borrow_mut().call((*right).borrow().constant.as_ref().unwrap().as_tuple(), scope.clone());
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "+" => {
-                match (*var_ref_cell).borrow().constant.as_ref().unwrap() {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Constant,
-                            constant: Some(Constant::Integer(i + (*right).borrow_mut().constant.as_ref().unwrap().as_integer())),
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "+=" => {
-                let constant;
-                {
-                    constant = var_ref_cell.as_ref().borrow().constant.clone().unwrap();
-                }
-                match constant {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        let result;
-                        {
-                            result = Some(Constant::Integer(i + (*right).borrow_mut().constant.as_ref().unwrap().as_integer()));
-                        }
-                        (*var_ref_cell).borrow_mut().constant = result;
-                        return var_ref_cell.clone();
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "-" => {
-                match (*var_ref_cell).borrow().constant.as_ref().unwrap() {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Constant,
-                            constant: Some(Constant::Integer(i - (*right).borrow_mut().constant.as_ref().unwrap().as_integer())),
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "-=" => {
-                let constant;
-                {
-                    constant = var_ref_cell.as_ref().borrow().constant.clone().unwrap();
-                }
-                match constant {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        let result;
-                        {
-                            result = Some(Constant::Integer(i - (*right).borrow_mut().constant.as_ref().unwrap().as_integer()));
-                        }
-                        (*var_ref_cell).borrow_mut().constant = result;
-                        return var_ref_cell.clone();
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "*" => {
-                match (*var_ref_cell).borrow().constant.as_ref().unwrap() {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Constant,
-                            constant: Some(Constant::Integer(i * (*right).borrow_mut().constant.as_ref().unwrap().as_integer())),
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "*=" => {
-                let constant;
-                {
-                    constant = var_ref_cell.as_ref().borrow().constant.clone().unwrap();
-                }
-                match constant {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        let result;
-                        {
-                            result = Some(Constant::Integer(i * (*right).borrow_mut().constant.as_ref().unwrap().as_integer()));
-                        }
-                        (*var_ref_cell).borrow_mut().constant = result;
-                        return var_ref_cell.clone();
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            "/" => {
-                match (*var_ref_cell).borrow().constant.as_ref().unwrap() {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Constant,
-                            constant: Some(Constant::Integer(i / (*right).borrow_mut().constant.as_ref().unwrap().as_integer())),
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
+/// Applies `+`, `-`, `*` or `/` to two constants.
+///
+/// Undefined operands give undefined. `+` concatenates as soon as one operand is a string. Integers stay integers, and
+/// mixing an integer with a float gives a float. Any other combination is an error.
+fn apply_arithmetic(operator: &Operator, hook: OperatorHook, left: &Constant, right: &Constant) -> Result<Constant, RuntimeError> {
+    match (left, right) {
+        (Constant::Undefined, _) | (_, Constant::Undefined) => {
+            return Ok(Constant::Undefined);
+        }
+        (Constant::String(_), _) | (_, Constant::String(_)) if matches!(hook, OperatorHook::Add) => {
+            let mut result = left.get_dump();
+            result.push_str(right.get_dump().as_str());
+            return Ok(Constant::String(result));
+        }
+        (Constant::Integer(l), Constant::Integer(r)) => {
+            return Ok(Constant::Integer(match hook {
+                OperatorHook::Add => l.wrapping_add(*r),
+                OperatorHook::Subtract => l.wrapping_sub(*r),
+                OperatorHook::Multiply => l.wrapping_mul(*r),
+                _ => {
+                    if *r == 0 {
+                        return Err(Diagnostic::error("Division by zero.").into());
                     }
-                };
-            }
-            "/=" => {
-                let constant;
-                {
-                    constant = var_ref_cell.as_ref().borrow().constant.clone().unwrap();
+                    l.wrapping_div(*r)
                 }
-                match constant {
-                    Constant::Undefined => {
-                        return construct_variable(Value {
-                            value_type: ValueType::Undefined,
-                            constant: None,
-                            variable: None,
-                        }, scope.clone());
-                    }
-                    Constant::Integer(i) => {
-                        let result;
-                        {
-                            result = Some(Constant::Integer(i / (*right).borrow_mut().constant.as_ref().unwrap().as_integer()));
-                        }
-                        (*var_ref_cell).borrow_mut().constant = result;
-                        return var_ref_cell.clone();
-                    }
-                    _ => {
-                        println!("Error: No such operator type.");
-                        panic!();
-                    }
-                };
-            }
-            _ => {
-                println!("Error: Unknown operator '{}'", operator);
-                panic!();
-            }
+            }));
+        }
+        (Constant::Integer(_), Constant::Float(_)) | (Constant::Float(_), Constant::Integer(_)) | (Constant::Float(_), Constant::Float(_)) => {
+            let l = left.as_float()?;
+            let r = right.as_float()?;
+            return Ok(Constant::Float(match hook {
+                OperatorHook::Add => l + r,
+                OperatorHook::Subtract => l - r,
+                OperatorHook::Multiply => l * r,
+                _ => l / r
+            }));
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Cannot apply operator '{}' to {} and {}.", operator.symbol, left.get_type_name(), right.get_type_name()).as_str()).into());
         }
     }
+}
 
-    fn apply_operator_right_vn(var_ref_cell: Rc<RefCell<Variable>>, right: &String, operator: &String, _accessible_variables: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>> {
-        match operator.as_str() {
-            "." => {
-                let deref = var_ref_cell.clone();
+/// Applies the prefix `-`, `+` or `!` to a constant.
+///
+/// `!` negates the truthiness of any value. `-` and `+` only apply to numbers, and give undefined for undefined like the
+/// arithmetic operators.
+fn apply_prefix_operator(operator: &Operator, operand: &Constant) -> Result<Constant, RuntimeError> {
+    match (operator.hook, operand) {
+        (OperatorHook::Not, _) => {
+            return Ok(Constant::Boolean(!operand.as_boolean()));
+        }
+        (OperatorHook::Negate | OperatorHook::Plus, Constant::Undefined) => {
+            return Ok(Constant::Undefined);
+        }
+        (OperatorHook::Negate, Constant::Integer(i)) => {
+            return Ok(Constant::Integer(i.wrapping_neg()));
+        }
+        (OperatorHook::Negate, Constant::Float(f)) => {
+            return Ok(Constant::Float(-f));
+        }
+        (OperatorHook::Plus, Constant::Integer(_) | Constant::Float(_)) => {
+            return Ok(operand.clone());
+        }
+        (OperatorHook::Negate | OperatorHook::Plus, _) => {
+            return Err(Diagnostic::error(format!("Cannot apply operator '{}' to {}.", operator.symbol, operand.get_type_name()).as_str()).into());
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Unknown prefix operator '{}'.", operator.symbol).as_str()).into());
+        }
+    }
+}
 
-                for field_var in deref.deref().borrow().members.iter() {
-                    if field_var.deref().borrow_mut().name.as_ref().unwrap().eq(right.as_str()) {
-                        return field_var.clone();
-                    }
-                }
+/// Equality never converts between types, values of different types are never equal. Integers and floats are both
+/// numbers, and compare by value.
+///
+/// Functions, tuples and arrays are only equal to themselves.
+fn are_constants_equal(left: &Constant, right: &Constant) -> bool {
+    return match (left, right) {
+        (Constant::Undefined, Constant::Undefined) => true,
+        (Constant::Null, Constant::Null) => true,
+        (Constant::Integer(l), Constant::Integer(r)) => l == r,
+        (Constant::Integer(_), Constant::Float(_)) | (Constant::Float(_), Constant::Integer(_)) | (Constant::Float(_), Constant::Float(_)) => {
+            left.as_float().ok() == right.as_float().ok()
+        }
+        (Constant::String(l), Constant::String(r)) => l == r,
+        (Constant::Boolean(l), Constant::Boolean(r)) => l == r,
+        (Constant::Function(l), Constant::Function(r)) => Rc::ptr_eq(l, r),
+        (Constant::Tuple(l), Constant::Tuple(r)) => Rc::ptr_eq(l, r),
+        (Constant::Array(l), Constant::Array(r)) => Rc::ptr_eq(l, r),
+        _ => false
+    };
+}
 
-                println!("Error: No such member in variable");
-                panic!();
-            }
-            _ => {
-                println!("Error: Unknown operator for variable names '{}'", operator);
-                panic!();
-            }
+/// Objects are equal when they have the same members with equal values, in any order. Other values are compared by
+/// their constants.
+fn are_variables_equal(left: &Variable, right: &Variable) -> bool {
+    return are_values_equal(left, right, &mut vec![]);
+}
+
+/// Compares two values, knowing the pairs of objects being compared around them. Comparing a pair again means the
+/// objects contain themselves, and they are equal if the rest of their members are.
+fn are_values_equal(left: &Variable, right: &Variable, comparing: &mut Vec<(usize, usize)>) -> bool {
+    if let (Some(Constant::Object(left)), Some(Constant::Object(right))) = (&left.constant, &right.constant) {
+        let pair = (Rc::as_ptr(left) as usize, Rc::as_ptr(right) as usize);
+        if Rc::ptr_eq(left, right) || comparing.contains(&pair) {
+            return true;
+        }
+
+        comparing.push(pair);
+        let right = right.deref().borrow();
+        let equal = left.deref().borrow().len() == right.len() && left.deref().borrow().iter().all(|member| {
+            let member = member.deref().borrow();
+            return match right.iter().find(|other| other.borrow().name == member.name) {
+                Some(other) => are_values_equal(&member, &other.deref().borrow(), comparing),
+                None => false
+            };
+        });
+        comparing.pop();
+        return equal;
+    }
+    return are_constants_equal(left.constant.as_ref().unwrap(), right.constant.as_ref().unwrap());
+}
+
+/// Orders two constants for `<`, `>`, `<=` and `>=`.
+///
+/// Numbers are ordered by value and strings lexicographically. Nothing is ordered relative to undefined (or to NaN),
+/// which is reported with `None` and makes every ordering comparison false. Ordering any other pair of types is an error.
+fn compare_constants(left: &Constant, right: &Constant) -> Result<Option<Ordering>, RuntimeError> {
+    match (left, right) {
+        (Constant::Undefined, _) | (_, Constant::Undefined) => {
+            return Ok(None);
+        }
+        (Constant::Integer(l), Constant::Integer(r)) => {
+            return Ok(Some(l.cmp(r)));
+        }
+        (Constant::Integer(_), Constant::Float(_)) | (Constant::Float(_), Constant::Integer(_)) | (Constant::Float(_), Constant::Float(_)) => {
+            return Ok(left.as_float()?.partial_cmp(&right.as_float()?));
+        }
+        (Constant::String(l), Constant::String(r)) => {
+            return Ok(Some(l.cmp(r)));
+        }
+        _ => {
+            return Err(Diagnostic::error(format!("Cannot compare {} with {}.", left.get_type_name(), right.get_type_name()).as_str()).into());
         }
     }
 }
 
-trait Convertible {
-    fn as_integer(&self) -> i64;
-    fn as_tuple(&self) -> Rc<RefCell<Tuple>>;
+/// Conversions of constants, failing when the constant has another type.
+pub trait Convertible {
+    fn as_integer(&self) -> Result<i64, RuntimeError>;
+    fn as_float(&self) -> Result<f64, RuntimeError>;
+    fn as_boolean(&self) -> bool;
+    fn as_tuple(&self) -> Result<Rc<RefCell<Tuple>>, RuntimeError>;
 }
 
 pub trait Callable {
-    fn call(&mut self, args: Rc<RefCell<Tuple>>, scope: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>>;
+    /// Natives can fail with a `RuntimeError`, the call locates it.
+    fn call(&self, args: Vec<Rc<RefCell<Variable>>>, environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError>;
     fn get_args(&self) -> Option<&Vec<String>>;
-    fn get_scope(&self) -> Option<Rc<RefCell<Scope>>>;
+    fn get_scope(&self) -> Option<Rc<Scope>>;
+
+    /// The environment the function captured, if it is a SysCode function.
+    fn get_closure(&self) -> Option<Rc<RefCell<Environment>>> {
+        return None;
+    }
 }
 
-impl Dumpable for Callable {
+impl Dumpable for dyn Callable {
     fn get_dump(&self) -> String {
         let mut str = "function( ".to_string();
         if self.get_args().is_some() {
@@ -480,7 +1582,7 @@ impl Dumpable for Callable {
         }
         str.push_str(")");
         if self.get_scope().is_some() {
-            str.push_str(self.get_scope().as_ref().unwrap().deref().borrow().get_dump().as_str());
+            str.push_str(self.get_scope().as_ref().unwrap().get_dump().as_str());
         }
         else {
             str.push_str("{Native Code}");
@@ -494,63 +1596,84 @@ impl Dumpable for Callable {
 }
 
 impl Convertible for Constant {
-    fn as_integer(&self) -> i64 {
+    fn as_integer(&self) -> Result<i64, RuntimeError> {
         match self {
             Constant::Integer(i) => {
-                return *i;
+                return Ok(*i);
             }
             _ => {
-                unimplemented!();
+                return Err(Diagnostic::error(format!("Expected an integer, found {}.", self.get_type_name()).as_str()).into());
             }
         };
     }
 
-    fn as_tuple(&self) -> Rc<RefCell<Tuple>> {
+    fn as_float(&self) -> Result<f64, RuntimeError> {
+        match self {
+            Constant::Integer(i) => {
+                return Ok(*i as f64);
+            }
+            Constant::Float(f) => {
+                return Ok(*f);
+            }
+            _ => {
+                return Err(Diagnostic::error(format!("Expected a number, found {}.", self.get_type_name()).as_str()).into());
+            }
+        };
+    }
+
+    /// Truthiness used by conditions : false, null, undefined, zero, NaN and the empty string are false, everything
+    /// else is true.
+    fn as_boolean(&self) -> bool {
+        return match self {
+            Constant::Undefined | Constant::Null => false,
+            Constant::Boolean(b) => *b,
+            Constant::Integer(i) => *i != 0,
+            Constant::Float(f) => *f != 0.0 && !f.is_nan(),
+            Constant::String(s) => !s.is_empty(),
+            Constant::Function(_) | Constant::Tuple(_) | Constant::Object(_) | Constant::Array(_) => true
+        };
+    }
+
+    fn as_tuple(&self) -> Result<Rc<RefCell<Tuple>>, RuntimeError> {
         match self {
             Constant::Tuple(t) => {
-                return t.clone();
+                return Ok(t.clone());
             }
             _ => {
-                unimplemented!();
+                return Err(Diagnostic::error(format!("Expected a tuple, found {}.", self.get_type_name()).as_str()).into());
             }
         };
     }
 }
 
 impl Callable for Function {
-    fn call(&mut self, args: Rc<RefCell<Tuple>>, scope: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>> {
-        let mut scope_to_exec = (*self.scope).borrow().clone();
+    fn call(&self, args: Vec<Rc<RefCell<Variable>>>, environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        // Variables the function doesn't declare are looked up where it was created, not where it is called.
+        let mut call_environment = Environment::new(Some(self.closure.clone().unwrap_or(environment)));
         for i in 0..self.args.len() {
-            if i >= (*args).borrow().expressions.len() {
-                scope_to_exec.accessible_variables.push(Rc::new(RefCell::new(Variable {
-                    name: Some(self.args.get(i).unwrap().clone()),
-                    constant: Some(Constant::Undefined),
-                    members: vec![]
-                })));
-            }
-            else {
-                let var = Rc::new(RefCell::new(Variable {
-                    name: Some(self.args.get(i).unwrap().clone()),
-                    constant: Some(Constant::Undefined),
-                    members: vec![]
-                }));
-                var.deref().borrow_mut().assign(execute_expression((*args).borrow().expressions.get(i).unwrap(), scope.clone()).to_variable(scope.clone()));
-                scope_to_exec.accessible_variables.push(var);
+            let var = Rc::new(RefCell::new(Variable {
+                name: Some(self.args.get(i).unwrap().clone()),
+                constant: Some(Constant::Undefined)
+            }));
+            if i < args.len() {
+                var.deref().borrow_mut().assign(args.get(i).unwrap().clone());
             }
+            call_environment.variables.push(var);
         }
 
-        scope_to_exec.parent = Some(scope.clone());
-        let scope_to_exec_rc = Rc::new(RefCell::new(scope_to_exec));
-        execute_scope(scope_to_exec_rc.clone());
-        if (*scope_to_exec_rc).borrow().return_value.is_some() {
-            return (*scope_to_exec_rc).borrow().return_value.as_ref().unwrap().clone();
-        }
-        else {
-            return Rc::new(RefCell::new(Variable {
-                name: None,
-                constant: Some(Constant::Undefined),
-                members: vec![]
-            }));
+        match execute_scope(&self.scope, Rc::new(RefCell::new(call_environment)))? {
+            ControlFlow::Return(result) => {
+                return Ok(result);
+            }
+            ControlFlow::Break | ControlFlow::Continue => {
+                return Err(Diagnostic::error("'break' and 'continue' can only be used inside a loop.").into());
+            }
+            ControlFlow::Normal(_) => {
+                return Ok(Rc::new(RefCell::new(Variable {
+                    name: None,
+                    constant: Some(Constant::Undefined)
+                })));
+            }
         }
     }
 
@@ -558,30 +1681,134 @@ impl Callable for Function {
         return Some(&self.args);
     }
 
-    fn get_scope(&self) -> Option<Rc<RefCell<Scope>>> {
+    fn get_scope(&self) -> Option<Rc<Scope>> {
         return Some(self.scope.clone());
     }
+
+    fn get_closure(&self) -> Option<Rc<RefCell<Environment>>> {
+        return self.closure.clone();
+    }
 }
 
 pub struct PrintFunction;
 
 impl Callable for PrintFunction {
-    fn call(&mut self, args: Rc<RefCell<Tuple>>, scope: Rc<RefCell<Scope>>) -> Rc<RefCell<Variable>> {
-        for e in (*args).borrow().expressions.iter() {
-            execute_expression(e, scope.clone()).to_variable(scope.clone()).deref().borrow().dump();
+    fn call(&self, args: Vec<Rc<RefCell<Variable>>>, _environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        for arg in args.iter() {
+            arg.deref().borrow().dump();
         }
-        return Rc::new(RefCell::new(Variable {
+        return Ok(Rc::new(RefCell::new(Variable {
             name: None,
-            constant: Some(Constant::Undefined),
-            members: vec![]
-        }))
+            constant: Some(Constant::Undefined)
+        })));
+    }
+
+    fn get_args(&self) -> Option<&Vec<String>> {
+        return None;
+    }
+
+    fn get_scope(&self) -> Option<Rc<Scope>> {
+        return None;
+    }
+}
+
+/// `Error(message)`, the object scripts throw, with `message` and `stack` members.
+pub struct ErrorFunction;
+
+impl Callable for ErrorFunction {
+    fn call(&self, args: Vec<Rc<RefCell<Variable>>>, _environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        let message = match args.first() {
+            Some(message) => message.deref().borrow().constant.as_ref().unwrap().get_dump(),
+            None => "".to_string()
+        };
+        return Ok(construct_variable_from_error(message));
+    }
+
+    fn get_args(&self) -> Option<&Vec<String>> {
+        return None;
+    }
+
+    fn get_scope(&self) -> Option<Rc<Scope>> {
+        return None;
+    }
+}
+
+/// `array.push(values...)`, appending the values and returning the new length.
+pub struct ArrayPushFunction {
+    pub elements: ArrayElements,
+}
+
+impl Callable for ArrayPushFunction {
+    fn call(&self, args: Vec<Rc<RefCell<Variable>>>, _environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        for arg in args.into_iter() {
+            let element = copy_value(arg);
+            self.elements.deref().borrow_mut().push(element);
+        }
+        return Ok(construct_variable_from_integer(self.elements.deref().borrow().len() as i64));
+    }
+
+    fn get_args(&self) -> Option<&Vec<String>> {
+        return None;
+    }
+
+    fn get_scope(&self) -> Option<Rc<Scope>> {
+        return None;
+    }
+}
+
+/// `array.pop()`, removing the last element and returning it, or undefined if the array is empty.
+pub struct ArrayPopFunction {
+    pub elements: ArrayElements,
+}
+
+impl Callable for ArrayPopFunction {
+    fn call(&self, _args: Vec<Rc<RefCell<Variable>>>, _environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        let element = self.elements.deref().borrow_mut().pop();
+        return Ok(element.unwrap_or_else(construct_variable_from_undefined));
+    }
+
+    fn get_args(&self) -> Option<&Vec<String>> {
+        return None;
+    }
+
+    fn get_scope(&self) -> Option<Rc<Scope>> {
+        return None;
+    }
+}
+
+/// `array.slice(start, end)`, a new array with copies of the elements from `start` up to `end` excluded.
+///
+/// `end` defaults to the length of the array, and both are clamped to it.
+pub struct ArraySliceFunction {
+    pub elements: ArrayElements,
+}
+
+impl Callable for ArraySliceFunction {
+    fn call(&self, args: Vec<Rc<RefCell<Variable>>>, _environment: Rc<RefCell<Environment>>) -> Result<Rc<RefCell<Variable>>, RuntimeError> {
+        let elements = self.elements.deref().borrow();
+        let mut bounds = [0, elements.len()];
+        for (bound, arg) in bounds.iter_mut().zip(args.iter()) {
+            let arg = arg.deref().borrow().constant.clone().unwrap();
+            if let Constant::Undefined = arg {
+                continue;
+            }
+            *bound = get_array_index(&arg)?.min(elements.len());
+        }
+
+        let mut slice: Vec<Rc<RefCell<Variable>>> = vec![];
+        if bounds[0] < bounds[1] {
+            for element in elements[bounds[0]..bounds[1]].iter() {
+                slice.push(copy_value(element.clone()));
+            }
+        }
+        return Ok(construct_variable_from_array(slice));
     }
 
     fn get_args(&self) -> Option<&Vec<String>> {
         return None;
     }
 
-    fn get_scope(&self) -> Option<Rc<RefCell<Scope>>> {
+    fn get_scope(&self) -> Option<Rc<Scope>> {
         return None;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,230 @@
+use crate::abstract_syntax_tree::{Scope, StatementKind, Constant, ParseError};
+use crate::executor::{self, Variable, Environment, Callable, PrintFunction, ErrorFunction, ControlFlow, RuntimeError, execute_statement};
+use crate::operators::OperatorTable;
+use crate::lexer::{self, SourceFile, Token};
+use crate::diagnostic::Diagnostic;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::{fmt, fs, io};
+
+/// Why running a source failed.
+pub enum Error {
+    /// The source couldn't be parsed, with every error found in it.
+    Syntax(Vec<ParseError>),
+    Runtime(RuntimeError),
+    /// The file named by the path couldn't be read.
+    Io(String, io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Error::Syntax(errors) => {
+                let rendered: Vec<String> = errors.iter().map(|error| error.render()).collect();
+                write!(f, "{}", rendered.join("\n\n"))
+            }
+            Error::Runtime(diagnostic) => write!(f, "{}", diagnostic),
+            Error::Io(path, error) => write!(f, "error: Cannot read file '{}': {}", path, error)
+        };
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self);
+    }
+}
+
+/// An embeddable SysCode interpreter.
+///
+/// It keeps a global environment alive between calls, so variables declared by one `eval` are visible to the next.
+pub struct Interpreter {
+    operators: OperatorTable,
+    /// The operators of the last tokenized source, kept apart until the source parses.
+    declared_operators: Option<OperatorTable>,
+    global_environment: Rc<RefCell<Environment>>,
+    natives: Vec<(String, Rc<RefCell<dyn Callable>>)>,
+    max_call_depth: usize,
+    max_stack_size: Option<usize>,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        let mut interpreter = Interpreter {
+            operators: OperatorTable::with_builtins(),
+            declared_operators: None,
+            global_environment: Rc::new(RefCell::new(Environment::new(None))),
+            natives: vec![],
+            max_call_depth: executor::DEFAULT_MAX_CALL_DEPTH,
+            max_stack_size: None,
+        };
+
+        interpreter.register_native("print", Rc::new(RefCell::new(PrintFunction)));
+        interpreter.register_native("Error", Rc::new(RefCell::new(ErrorFunction)));
+
+        return interpreter;
+    }
+
+    /// Makes a Rust function callable from scripts under the given global name.
+    pub fn register_native(&mut self, name: &str, function: Rc<RefCell<dyn Callable>>) {
+        self.natives.push((name.to_string(), function.clone()));
+        self.set_global(name, Rc::new(RefCell::new(Variable {
+            name: None,
+            constant: Some(Constant::Function(function))
+        })));
+    }
+
+    /// Sets how many function calls can be nested before a call fails with a stack overflow error, which scripts can
+    /// catch.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    pub fn get_max_call_depth(&self) -> usize {
+        return self.max_call_depth;
+    }
+
+    /// Sets how many bytes of the native stack scripts can use before a call or an expression fails with a stack
+    /// overflow error, whatever the number of nested calls.
+    ///
+    /// By default scripts can use the stack of the thread running them but its last 256 KiB, or 1 MiB where the size of
+    /// that stack is unknown, which is safe on any thread with at least 2 MiB of stack.
+    pub fn set_max_stack_size(&mut self, size: usize) {
+        self.max_stack_size = Some(size);
+    }
+
+    /// The size set with `set_max_stack_size`, if any.
+    pub fn get_max_stack_size(&self) -> Option<usize> {
+        return self.max_stack_size;
+    }
+
+    pub fn get_operators(&self) -> &OperatorTable {
+        return &self.operators;
+    }
+
+    pub fn get_natives(&self) -> &Vec<(String, Rc<RefCell<dyn Callable>>)> {
+        return &self.natives;
+    }
+
+    /// Splits the source into tokens, along with the operators it declares.
+    ///
+    /// The declared operators are added to the operator table once `parse` succeeds on the tokens. The name is what
+    /// errors use to refer to the source, usually its path.
+    pub fn tokenize(&mut self, name: &str, source: &str) -> Vec<Token> {
+        let source = Arc::new(SourceFile {
+            name: name.to_string(),
+            text: source.to_string()
+        });
+        let mut operators = self.operators.clone();
+        let mut tokens = lexer::tokenize(&source, &operators.get_symbols());
+        if operators.declare_custom_operators(&tokens) {
+            tokens = lexer::tokenize(&source, &operators.get_symbols());
+        }
+        self.declared_operators = Some(operators);
+        return tokens;
+    }
+
+    /// Parses the tokens of a whole source, returning all the syntax errors found in it if there are any.
+    ///
+    /// The operators the source declares are kept for the next sources only if it parses.
+    pub fn parse(&mut self, tokens: &Vec<Token>) -> Result<Scope, Vec<ParseError>> {
+        // Nested blocks and expressions are parsed by nested calls, limited like those of a running script.
+        self.set_stack_limits();
+        let operators = self.declared_operators.take().unwrap_or_else(|| self.operators.clone());
+        let scope = Scope::parse_program(tokens, &operators)?;
+        self.operators = operators;
+        return Ok(scope);
+    }
+
+    /// Runs the statements of a parsed scope in the global environment.
+    ///
+    /// Returns the value of the last statement if it is an expression. Statements run before an error keep their
+    /// effects.
+    pub fn execute(&mut self, scope: &Scope) -> Result<Option<Rc<RefCell<Variable>>>, RuntimeError> {
+        self.set_stack_limits();
+        let global_environment = self.global_environment.clone();
+        let mut last_value = None;
+        for statement in scope.statements.iter() {
+            last_value = None;
+            match execute_statement(statement, global_environment.clone())? {
+                ControlFlow::Normal(result) => {
+                    if let StatementKind::Expression(_) = statement.kind {
+                        if let Some(result) = result {
+                            last_value = Some(result.to_variable(global_environment.clone()));
+                        }
+                    }
+                }
+                // A top level return has no function to leave, only the rest of the input.
+                ControlFlow::Return(result) => {
+                    last_value = Some(result);
+                    break;
+                }
+                ControlFlow::Break | ControlFlow::Continue => {
+                    return Err(Diagnostic::error("'break' and 'continue' can only be used inside a loop.").at(statement.span.as_ref()).into());
+                }
+            }
+        }
+        return Ok(last_value);
+    }
+
+    fn set_stack_limits(&self) {
+        executor::set_max_call_depth(self.max_call_depth);
+        executor::set_max_stack_size(self.max_stack_size);
+        executor::mark_stack_start();
+    }
+
+    pub fn eval(&mut self, source: &str) -> Result<Option<Rc<RefCell<Variable>>>, Error> {
+        return self.eval_named("<eval>", source);
+    }
+
+    /// Like `eval`, errors referring to the source by the given name.
+    pub fn eval_named(&mut self, name: &str, source: &str) -> Result<Option<Rc<RefCell<Variable>>>, Error> {
+        let tokens = self.tokenize(name, source);
+        let scope = self.parse(&tokens).map_err(Error::Syntax)?;
+        return self.execute(&scope).map_err(Error::Runtime);
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Option<Rc<RefCell<Variable>>>, Error> {
+        let name = path.as_ref().to_string_lossy().to_string();
+        let source = match fs::read_to_string(path.as_ref()) {
+            Ok(source) => source,
+            Err(error) => return Err(Error::Io(name, error))
+        };
+        return self.eval_named(name.as_str(), source.as_str());
+    }
+
+    pub fn get_global_environment(&self) -> Rc<RefCell<Environment>> {
+        return self.global_environment.clone();
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Rc<RefCell<Variable>>> {
+        return (*self.global_environment).borrow().get_variable(name);
+    }
+
+    /// Declares a global variable, or assigns the value to it if it already exists.
+    pub fn set_global(&mut self, name: &str, value: Rc<RefCell<Variable>>) {
+        if let Some(variable) = self.get_global(name) {
+            variable.deref().borrow_mut().assign(value);
+            return;
+        }
+
+        let variable = Rc::new(RefCell::new(Variable {
+            name: Some(name.to_string()),
+            constant: None
+        }));
+        variable.deref().borrow_mut().assign(value);
+        (*self.global_environment).borrow_mut().variables.push(variable);
+    }
+}
+
+impl Drop for Interpreter {
+    /// Global functions hold the global environment, which holds them back, so the cycle has to be broken by hand.
+    ///
+    /// Values the interpreter returned stay valid, but functions among them no longer see the global variables.
+    fn drop(&mut self) {
+        (*self.global_environment).borrow_mut().variables.clear();
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// A source text and the name errors refer to it by, usually its path.
+///
+/// It is shared with an `Arc` so diagnostics pointing into it can be sent to another thread.
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
+
+/// A range of a source file.
+///
+/// `start` and `end` are byte offsets, `line` and `column` locate `start` and both count from 1.
+pub struct Span {
+    pub source: Arc<SourceFile>,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Clone for Span {
+    fn clone(&self) -> Self {
+        return Span {
+            source: self.source.clone(),
+            start: self.start,
+            end: self.end,
+            line: self.line,
+            column: self.column,
+        };
+    }
+}
+
+impl Span {
+    /// The span going from the start of this one to the end of the other one.
+    pub fn to(&self, other: &Span) -> Span {
+        let mut span = self.clone();
+        if other.end > span.end {
+            span.end = other.end;
+        }
+        return span;
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}:{}:{}", self.source.name, self.line, self.column);
+    }
+}
+
+pub enum TokenKind {
+    Identifier,
+    Number,
+    /// A quoted string, the text of the token being its content without the quotes.
+    String,
+    /// A string missing its closing quote.
+    UnterminatedString,
+    /// An operator or a punctuation sign.
+    Symbol,
+}
+
+impl Copy for TokenKind {}
+
+impl Clone for TokenKind {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Clone for Token {
+    fn clone(&self) -> Self {
+        return Token {
+            text: self.text.clone(),
+            kind: self.kind,
+            span: self.span.clone(),
+        };
+    }
+}
+
+impl Token {
+    pub fn as_str(&self) -> &str {
+        return self.text.as_str();
+    }
+
+    /// Whether the token is the given symbol, identifier or number, strings never match.
+    pub fn is(&self, text: &str) -> bool {
+        return !matches!(self.kind, TokenKind::String | TokenKind::UnterminatedString) && self.text.eq(text);
+    }
+}
+
+impl PartialEq<str> for Token {
+    fn eq(&self, other: &str) -> bool {
+        return self.is(other);
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.text);
+    }
+}
+
+/// Splits a source file into tokens.
+///
+/// Symbols are matched greedily, the longest one starting at a position wins. Anything that is neither whitespace, a
+/// quote nor the start of a symbol is part of an identifier or a number, and a number followed by `.` and a digit is a
+/// decimal.
+pub fn tokenize(source: &Arc<SourceFile>, symbols: &Vec<String>) -> Vec<Token> {
+    let text = source.text.as_str();
+    let mut tokens: Vec<Token> = vec![];
+
+    // The column is counted as the characters are consumed, counting it from the start of the line for each token would
+    // be slow on long lines.
+    let mut line = 1;
+    let mut column = 1;
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        let c = rest.chars().next().unwrap();
+        let span = |start: usize, end: usize| Span {
+            source: source.clone(),
+            start,
+            end,
+            line,
+            column,
+        };
+
+        if c.is_whitespace() {
+            index += c.len_utf8();
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+            continue;
+        }
+
+        if c == '"' || c == '\'' {
+            let content_start = index + 1;
+            let (kind, content_end, end) = match text[content_start..].find(c) {
+                Some(length) => (TokenKind::String, content_start + length, content_start + length + 1),
+                None => (TokenKind::UnterminatedString, text.len(), text.len())
+            };
+            tokens.push(Token {
+                text: text[content_start..content_end].to_string(),
+                kind,
+                span: span(index, end),
+            });
+
+            // Strings can span several lines.
+            for c in text[index..end].chars() {
+                if c == '\n' {
+                    line += 1;
+                    column = 1;
+                } else {
+                    column += 1;
+                }
+            }
+            index = end;
+            continue;
+        }
+
+        if let Some(symbol) = get_longest_symbol(rest, symbols) {
+            tokens.push(Token {
+                text: symbol.to_string(),
+                kind: TokenKind::Symbol,
+                span: span(index, index + symbol.len()),
+            });
+            index += symbol.len();
+            column += symbol.chars().count();
+            continue;
+        }
+
+        let start = index;
+        let is_number = c.is_ascii_digit();
+        while index < text.len() {
+            let rest = &text[index..];
+            let c = rest.chars().next().unwrap();
+            if is_number && c == '.' && text[start..index].chars().all(|c| c.is_ascii_digit())
+                && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+                index += 1;
+                continue;
+            }
+            if c.is_whitespace() || c == '"' || c == '\'' || get_longest_symbol(rest, symbols).is_some() {
+                break;
+            }
+            index += c.len_utf8();
+        }
+        tokens.push(Token {
+            text: text[start..index].to_string(),
+            kind: if is_number { TokenKind::Number } else { TokenKind::Identifier },
+            span: span(start, index),
+        });
+        column += text[start..index].chars().count();
+    }
+
+    return tokens;
+}
+
+fn get_longest_symbol<'a>(text: &str, symbols: &'a Vec<String>) -> Option<&'a str> {
+    let mut longest: Option<&'a str> = None;
+    for symbol in symbols.iter() {
+        if !symbol.is_empty() && text.starts_with(symbol.as_str()) && (longest.is_none() || symbol.len() > longest.unwrap().len()) {
+            longest = Some(symbol.as_str());
+        }
+    }
+    return longest;
+}
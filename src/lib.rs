@@ -0,0 +1,24 @@
+#![allow(
+    clippy::needless_return,
+    clippy::ptr_arg,
+    clippy::single_char_add_str,
+    clippy::unnecessary_unwrap,
+    clippy::upper_case_acronyms,
+    clippy::enum_variant_names,
+    clippy::wrong_self_convention,
+    clippy::collapsible_match,
+    clippy::new_without_default
+)]
+
+pub mod abstract_syntax_tree;
+pub mod executor;
+pub mod constructors;
+pub mod operators;
+pub mod lexer;
+pub mod diagnostic;
+mod interpreter;
+mod collector;
+#[cfg(test)]
+mod tests;
+
+pub use crate::interpreter::{Interpreter, Error};
@@ -1,87 +1,126 @@
-mod abstract_syntax_tree;
-mod executor;
-mod constructors;
-use code_tokenizer::get_tokens;
-use crate::abstract_syntax_tree::*;
-use std::borrow::{BorrowMut, Borrow};
-use crate::executor::{VVA};
-use std::cell::{RefCell, Ref};
-use std::rc::Rc;
-use crate::executor::Variable;
+#![allow(clippy::needless_return, clippy::ptr_arg)]
+
+mod repl;
+use syscode::Interpreter;
+use syscode::abstract_syntax_tree::Dumpable;
+use syscode::constructors::{construct_variable_from_array, construct_variable_from_string};
+use std::{env, fs, process, thread};
+
+/// The stack of the thread running scripts. Each nested call of a script function takes a good part of it, this is
+/// enough for the default call depth limit even in a debug build.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// An interpreter allowed to use most of the stack of the thread running scripts.
+fn create_interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_max_stack_size(STACK_SIZE / 2);
+    return interpreter;
+}
+
+/// Debug output requested on the command line.
+struct DumpOptions {
+    tokens: bool,
+    ast: bool,
+    scope: bool,
+}
 
+impl DumpOptions {
+    fn is_empty(&self) -> bool {
+        return !self.tokens && !self.ast && !self.scope;
+    }
+}
+
+/// Runs the script, the arguments following its path being the strings of the global `args` array.
+fn run(path: &str, input_string: String, script_args: &[String], dump_options: &DumpOptions) {
+    let mut interpreter = create_interpreter();
+    let script_args = script_args.iter().map(|arg| construct_variable_from_string(arg.clone())).collect();
+    interpreter.set_global("args", construct_variable_from_array(script_args));
+    let tokens = interpreter.tokenize(path, input_string.as_str());
+    if dump_options.tokens {
+        for token in tokens.iter() {
+            println!("{}:{} {}", token.span.line, token.span.column, token);
+        }
+    }
+
+    let parsed = match interpreter.parse(&tokens) {
+        Ok(parsed) => parsed,
+        Err(errors) => {
+            for error in errors.iter() {
+                eprintln!("{}\n", error);
+            }
+            eprintln!("error: Could not parse '{}' due to {} previous error{}.", path, errors.len(), if errors.len() == 1 { "" } else { "s" });
+            process::exit(1);
+        }
+    };
+    if dump_options.ast {
+        parsed.dump();
+    }
+
+    // Dumping the tokens or the tree alone is a dry run, the scope can only be dumped once executed.
+    if !dump_options.is_empty() && !dump_options.scope {
+        return;
+    }
+
+    let result = interpreter.execute(&parsed);
+
+    if dump_options.scope {
+        for variable in (*interpreter.get_global_environment()).borrow().variables.iter() {
+            (**variable).borrow().dump();
+        }
+    }
+
+    if let Err(diagnostic) = result {
+        eprintln!("{}", diagnostic);
+        process::exit(1);
+    }
+}
 
 fn main() {
-    let input_string = "\
-    let my_variable = function(a, b, c){\
-        return a + b + c;
+    let command = thread::Builder::new().stack_size(STACK_SIZE).spawn(run_command_line).unwrap();
+    if command.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn run_command_line() {
+    let args: Vec<String> = env::args().collect();
+    let mut dump_options = DumpOptions {
+        tokens: false,
+        ast: false,
+        scope: false,
     };
-    let my_variable_3 = my_variable(1, 2, 3);
-    print(my_variable_3)".to_string();
-    let operators = vec![
-        "+".to_string(),
-        "-".to_string(),
-        "*".to_string(),
-        "/".to_string(),
-
-        "+=".to_string(),
-        "-=".to_string(),
-        "*=".to_string(),
-        "/=".to_string(),
-
-        "=".to_string(),
-
-        "==".to_string(),
-        "!=".to_string(),
-        "<".to_string(),
-        ">".to_string(),
-
-        "(".to_string(),
-        ")".to_string(),
-        "{".to_string(),
-        "}".to_string(),
-
-        ".".to_string(),
-        ",".to_string(),
-
-        ";".to_string()
-    ];
-
-    let operator_priorities = vec![
-        0,  // +
-        0,  // -
-        1,  // *
-        1,  // /
-
-        -1, // +=
-        -1, // -=
-        -1, // *=
-        -1, // /=
-
-        -3, // =
-
-        -2, // ==
-        -2, // !=
-        -2, // <
-        -2, // >
-
-        2,  // (
-        0,  // )
-        0,  // {
-        0,  // }
-
-        2, // .
-        0, // ,
-
-        0  // ;
-    ];
-    let tokens = get_tokens(input_string, operators.clone());
-    let mut scope = Scope::parse(tokens.borrow(), operators.borrow(), operator_priorities.borrow(), 0.borrow_mut(), tokens.len() as i64);
-
-    scope.accessible_variables.push(Rc::new(RefCell::new(Variable{
-        name: Some("print".to_string()),
-        constant: Some(Constant::Function(Rc::new(RefCell::new(executor::PrintFunction)))),
-        members: vec![]
-    })));
-
-    let value = executor::execute_scope(Rc::new(RefCell::new(scope)));
+
+    let mut path_index = 1;
+    while path_index < args.len() {
+        match args[path_index].as_str() {
+            "--dump-tokens" => dump_options.tokens = true,
+            "--dump-ast" => dump_options.ast = true,
+            "--dump-scope" => dump_options.scope = true,
+            _ => break
+        }
+        path_index += 1;
+    }
+
+    if path_index >= args.len() {
+        eprintln!("Usage: syscode [--dump-tokens] [--dump-ast] [--dump-scope] <file.sys> [args...]");
+        eprintln!("       syscode repl");
+        process::exit(1);
+    }
+
+    // A script named `repl` is run when flags or arguments come with it.
+    if args.len() == 2 && args[1].eq("repl") {
+        repl::run_repl();
+        return;
+    }
+
+    let path = &args[path_index];
+    let input_string = match fs::read_to_string(path) {
+        Ok(input_string) => input_string,
+        Err(error) => {
+            eprintln!("Error: Cannot read file '{}': {}", path, error);
+            process::exit(1);
+        }
+    };
+
+    run(path, input_string, &args[path_index + 1..], &dump_options);
 }
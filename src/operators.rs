@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use crate::lexer::{Token, TokenKind};
+
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+impl Copy for Associativity {}
+
+impl Clone for Associativity {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+pub enum Arity {
+    Prefix,
+    Infix,
+    Postfix,
+}
+
+impl Copy for Arity {}
+
+impl Clone for Arity {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// What the executor does when it evaluates an operator.
+pub enum OperatorHook {
+    Assign,
+    Call,
+    Member,
+    /// `object["name"]`, the member named by the string between the brackets.
+    Index,
+
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+
+    Negate,
+    Plus,
+    Not,
+
+    /// Calls the function a script bound to the operator with an `operator` declaration.
+    Custom,
+}
+
+impl OperatorHook {
+    /// The member an object can define to overload the operator.
+    ///
+    /// Compound assignments use the overload of their arithmetic operator and assign its result.
+    pub fn get_overload_name(&self) -> Option<&'static str> {
+        return match self {
+            OperatorHook::Add | OperatorHook::AddAssign => Some("__add__"),
+            OperatorHook::Subtract | OperatorHook::SubtractAssign => Some("__sub__"),
+            OperatorHook::Multiply | OperatorHook::MultiplyAssign => Some("__mul__"),
+            OperatorHook::Divide | OperatorHook::DivideAssign => Some("__div__"),
+            OperatorHook::Equal => Some("__eq__"),
+            OperatorHook::NotEqual => Some("__ne__"),
+            OperatorHook::Less => Some("__lt__"),
+            OperatorHook::Greater => Some("__gt__"),
+            OperatorHook::LessEqual => Some("__le__"),
+            OperatorHook::GreaterEqual => Some("__ge__"),
+            _ => None
+        };
+    }
+}
+
+impl Copy for OperatorHook {}
+
+impl Clone for OperatorHook {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+pub struct Operator {
+    pub symbol: String,
+    pub priority: i32,
+    pub associativity: Associativity,
+    pub arity: Arity,
+    pub hook: OperatorHook,
+}
+
+impl Clone for Operator {
+    fn clone(&self) -> Self {
+        return Operator {
+            symbol: self.symbol.clone(),
+            priority: self.priority,
+            associativity: self.associativity,
+            arity: self.arity,
+            hook: self.hook,
+        };
+    }
+}
+
+/// Every operator the parser and the executor know about, plus the punctuation the tokenizer has to split on.
+///
+/// A higher priority binds tighter. A symbol can be both a prefix operator and an infix or postfix one, the parser
+/// telling them apart by whether they follow a value.
+pub struct OperatorTable {
+    operators: Vec<Operator>,
+    operator_indices: HashMap<String, usize>,
+    prefix_operator_indices: HashMap<String, usize>,
+    punctuation: Vec<String>,
+}
+
+impl Clone for OperatorTable {
+    fn clone(&self) -> Self {
+        return OperatorTable {
+            operators: self.operators.clone(),
+            operator_indices: self.operator_indices.clone(),
+            prefix_operator_indices: self.prefix_operator_indices.clone(),
+            punctuation: self.punctuation.clone(),
+        };
+    }
+}
+
+impl OperatorTable {
+    pub fn new() -> OperatorTable {
+        return OperatorTable {
+            operators: vec![],
+            operator_indices: HashMap::new(),
+            prefix_operator_indices: HashMap::new(),
+            punctuation: vec![],
+        };
+    }
+
+    pub fn with_builtins() -> OperatorTable {
+        let mut table = OperatorTable::new();
+
+        table.add_infix("+", 0, Associativity::Left, OperatorHook::Add);
+        table.add_infix("-", 0, Associativity::Left, OperatorHook::Subtract);
+        table.add_infix("*", 1, Associativity::Left, OperatorHook::Multiply);
+        table.add_infix("/", 1, Associativity::Left, OperatorHook::Divide);
+
+        table.add_infix("+=", -1, Associativity::Right, OperatorHook::AddAssign);
+        table.add_infix("-=", -1, Associativity::Right, OperatorHook::SubtractAssign);
+        table.add_infix("*=", -1, Associativity::Right, OperatorHook::MultiplyAssign);
+        table.add_infix("/=", -1, Associativity::Right, OperatorHook::DivideAssign);
+
+        table.add_infix("=", -3, Associativity::Right, OperatorHook::Assign);
+
+        table.add_infix("==", -2, Associativity::Left, OperatorHook::Equal);
+        table.add_infix("!=", -2, Associativity::Left, OperatorHook::NotEqual);
+        table.add_infix("<", -2, Associativity::Left, OperatorHook::Less);
+        table.add_infix(">", -2, Associativity::Left, OperatorHook::Greater);
+        table.add_infix("<=", -2, Associativity::Left, OperatorHook::LessEqual);
+        table.add_infix(">=", -2, Associativity::Left, OperatorHook::GreaterEqual);
+
+        table.add_prefix("-", 2, OperatorHook::Negate);
+        table.add_prefix("+", 2, OperatorHook::Plus);
+        table.add_prefix("!", 2, OperatorHook::Not);
+
+        // The arguments of a call are parsed as a tuple on the right of "(", up to the closing parenthesis.
+        table.add_postfix("(", 3, OperatorHook::Call);
+        table.add_infix(".", 3, Associativity::Left, OperatorHook::Member);
+        table.add_postfix("[", 3, OperatorHook::Index);
+
+        table.add_punctuation(")");
+        table.add_punctuation("]");
+        table.add_punctuation(":");
+        table.add_punctuation("{");
+        table.add_punctuation("}");
+        table.add_punctuation(",");
+        table.add_punctuation(";");
+
+        return table;
+    }
+
+    /// Adds an operator, replacing any operator with the same symbol and the same position relative to its operands.
+    ///
+    /// Infix and postfix operators both follow a value, so one replaces the other.
+    pub fn add(&mut self, operator: Operator) {
+        let indices = match operator.arity {
+            Arity::Prefix => &mut self.prefix_operator_indices,
+            Arity::Infix | Arity::Postfix => &mut self.operator_indices
+        };
+        if let Some(index) = indices.get(operator.symbol.as_str()) {
+            self.operators[*index] = operator;
+            return;
+        }
+
+        indices.insert(operator.symbol.clone(), self.operators.len());
+        self.operators.push(operator);
+    }
+
+    pub fn add_infix(&mut self, symbol: &str, priority: i32, associativity: Associativity, hook: OperatorHook) {
+        self.add(Operator {
+            symbol: symbol.to_string(),
+            priority,
+            associativity,
+            arity: Arity::Infix,
+            hook,
+        });
+    }
+
+    pub fn add_prefix(&mut self, symbol: &str, priority: i32, hook: OperatorHook) {
+        self.add(Operator {
+            symbol: symbol.to_string(),
+            priority,
+            associativity: Associativity::Right,
+            arity: Arity::Prefix,
+            hook,
+        });
+    }
+
+    pub fn add_postfix(&mut self, symbol: &str, priority: i32, hook: OperatorHook) {
+        self.add(Operator {
+            symbol: symbol.to_string(),
+            priority,
+            associativity: Associativity::Left,
+            arity: Arity::Postfix,
+            hook,
+        });
+    }
+
+    pub fn add_punctuation(&mut self, symbol: &str) {
+        if !self.is_punctuation(symbol) {
+            self.punctuation.push(symbol.to_string());
+        }
+    }
+
+    /// The infix or postfix operator with the symbol, the one following a value.
+    pub fn get(&self, symbol: &str) -> Option<&Operator> {
+        return match self.operator_indices.get(symbol) {
+            Some(index) => Some(&self.operators[*index]),
+            None => None
+        };
+    }
+
+    /// The prefix operator with the symbol, the one preceding a value.
+    pub fn get_prefix(&self, symbol: &str) -> Option<&Operator> {
+        return match self.prefix_operator_indices.get(symbol) {
+            Some(index) => Some(&self.operators[*index]),
+            None => None
+        };
+    }
+
+    pub fn is_punctuation(&self, symbol: &str) -> bool {
+        return self.punctuation.iter().any(|p| p.eq(symbol));
+    }
+
+    pub fn get_operators(&self) -> &Vec<Operator> {
+        return &self.operators;
+    }
+
+    /// All the symbols the tokenizer has to split on.
+    pub fn get_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.operators.iter().map(|o| o.symbol.clone()).collect();
+        symbols.extend(self.punctuation.iter().cloned());
+        return symbols;
+    }
+
+    /// Registers the operators declared in a token stream with `operator <symbol> (a, b) priority <n> { ... }`.
+    ///
+    /// This has to happen before parsing, since the declared symbols change both how the source is tokenized and how
+    /// the expressions using them are grouped. Returns true if a new symbol was added, in which case the source needs
+    /// to be tokenized again. Malformed declarations are ignored here and reported by the parser.
+    pub fn declare_custom_operators(&mut self, tokens: &Vec<Token>) -> bool {
+        let mut new_symbol = false;
+        for i in 0..tokens.len() {
+            if !matches!(tokens[i].kind, TokenKind::Identifier) || !tokens[i].eq("operator") {
+                continue;
+            }
+
+            let (symbol, mut j) = get_declared_symbol(tokens, i + 1);
+            if !is_valid_custom_symbol(symbol.as_str()) {
+                continue;
+            }
+
+            while j < tokens.len() && !tokens[j].eq(")") {
+                j += 1;
+            }
+            let priority = match get_declared_priority(tokens, j + 1) {
+                Some(priority) => priority,
+                None => continue
+            };
+
+            if let Some(operator) = self.get(symbol.as_str()) {
+                if let OperatorHook::Custom = operator.hook {} else {
+                    continue;
+                }
+            } else {
+                new_symbol = true;
+            }
+
+            self.add_infix(symbol.as_str(), priority, Associativity::Left, OperatorHook::Custom);
+        }
+        return new_symbol;
+    }
+
+    pub fn get_max_priority(&self) -> i32 {
+        return self.operators.iter().map(|o| o.priority).max().unwrap_or(0);
+    }
+
+    pub fn get_min_priority(&self) -> i32 {
+        return self.operators.iter().map(|o| o.priority).min().unwrap_or(0);
+    }
+}
+
+pub fn is_valid_custom_symbol(symbol: &str) -> bool {
+    return !symbol.is_empty() && symbol.chars().all(|c| c.is_ascii_punctuation() && !"(){}[],;\"'_".contains(c));
+}
+
+/// Reads the symbol of `operator <symbol> (` starting at the given token, returning it with the index of the `(`.
+///
+/// Symbols that are not declared yet are split into several tokens.
+pub fn get_declared_symbol(tokens: &Vec<Token>, index: usize) -> (String, usize) {
+    let mut symbol = "".to_string();
+    let mut index = index;
+    while index < tokens.len() && !tokens[index].eq("(") {
+        symbol.push_str(tokens[index].as_str());
+        index += 1;
+    }
+    return (symbol, index);
+}
+
+/// Reads `priority <n>` starting at the given token, `n` being a possibly negative integer.
+pub fn get_declared_priority(tokens: &Vec<Token>, index: usize) -> Option<i32> {
+    if index >= tokens.len() || !tokens[index].eq("priority") {
+        return None;
+    }
+
+    let mut index = index + 1;
+    let mut sign = 1;
+    if index < tokens.len() && tokens[index].eq("-") {
+        sign = -1;
+        index += 1;
+    }
+    if index >= tokens.len() {
+        return None;
+    }
+
+    return match tokens[index].text.parse::<i32>() {
+        Ok(priority) => Some(sign * priority),
+        Err(_) => None
+    };
+}
@@ -0,0 +1,84 @@
+use syscode::Error;
+use syscode::lexer::{Token, TokenKind};
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Counts how many parentheses, brackets and braces are still open at the end of the tokens.
+fn get_open_delimiters(tokens: &Vec<Token>) -> i64 {
+    let mut open = 0;
+    for token in tokens.iter() {
+        if !matches!(token.kind, TokenKind::Symbol) {
+            continue;
+        }
+        match token.as_str() {
+            "(" | "[" | "{" => open += 1,
+            ")" | "]" | "}" => open -= 1,
+            _ => {}
+        }
+    }
+    return open;
+}
+
+pub fn run_repl() {
+    let mut interpreter = crate::create_interpreter();
+
+    // Panics are reported below, the default hook would only add noise.
+    panic::set_hook(Box::new(|_| {}));
+
+    let stdin = io::stdin();
+    let mut input = String::new();
+    loop {
+        if input.is_empty() {
+            print!("> ");
+        } else {
+            print!("... ");
+        }
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("Error: Cannot read input: {}", error);
+                break;
+            }
+        }
+        input.push_str(line.as_str());
+
+        let tokens = interpreter.tokenize("<repl>", input.as_str());
+        if get_open_delimiters(&tokens) > 0 {
+            continue;
+        }
+        input.clear();
+        if tokens.is_empty() {
+            continue;
+        }
+
+        // Errors in the input are diagnostics, a panic is a bug of the interpreter but shouldn't end the session.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let result = match interpreter.parse(&tokens) {
+                Ok(scope) => interpreter.execute(&scope).map_err(Error::Runtime),
+                Err(errors) => Err(Error::Syntax(errors))
+            };
+            match result {
+                Ok(Some(variable)) => {
+                    let variable = (*variable).borrow();
+                    if variable.constant.is_some() {
+                        println!("{}", variable.get_value_dump());
+                    }
+                }
+                Ok(None) => {}
+                Err(error) => println!("{}", error)
+            }
+        }));
+        if let Err(payload) = result {
+            if let Some(message) = payload.downcast_ref::<&str>() {
+                println!("Internal error: {}", message);
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                println!("Internal error: {}", message);
+            }
+        }
+    }
+    println!();
+}
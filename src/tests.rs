@@ -0,0 +1,448 @@
+use crate::{Interpreter, Error};
+use crate::abstract_syntax_tree::Constant;
+use crate::collector;
+use std::rc::Rc;
+
+/// The dump of the value of the last expression of the source.
+fn eval(source: &str) -> String {
+    let mut interpreter = Interpreter::new();
+    return match interpreter.eval(source) {
+        Ok(Some(value)) => value.borrow().get_value_dump(),
+        Ok(None) => "".to_string(),
+        Err(error) => panic!("{}", error)
+    };
+}
+
+/// The messages of the errors the source fails with.
+fn eval_errors(source: &str) -> Vec<String> {
+    let mut interpreter = Interpreter::new();
+    return match interpreter.eval(source) {
+        Ok(_) => panic!("'{}' didn't fail", source),
+        Err(Error::Syntax(errors)) => errors.iter().map(|error| error.message.clone()).collect(),
+        Err(Error::Runtime(error)) => vec![error.diagnostic.message.clone()],
+        Err(Error::Io(path, _)) => panic!("unexpected read of '{}'", path)
+    };
+}
+
+#[test]
+fn arithmetic_groups_by_priority_then_from_the_left() {
+    assert_eq!(eval("2 + 3 * 4"), "14");
+    assert_eq!(eval("10 - 3 - 2"), "5");
+    assert_eq!(eval("24 / 4 / 2"), "3");
+    assert_eq!(eval("(2 + 3) * 4"), "20");
+}
+
+#[test]
+fn assignments_group_from_the_right() {
+    assert_eq!(eval("let a = 0; let b = 0; a = b = 3; a + b"), "6");
+    assert_eq!(eval("let a = 1; let b = 2; a += b += 3; a"), "6");
+}
+
+#[test]
+fn prefix_operators_bind_tighter_than_multiplication_but_looser_than_members() {
+    assert_eq!(eval("let a = { b: 2 }; -a.b"), "-2");
+    assert_eq!(eval("-2 * 3"), "-6");
+    // `-(x * 2)` would call the overload and negate its result.
+    let errors = eval_errors("let x = { __mul__: function(l, r) { return 3; } }; -x * 2;");
+    assert_eq!(errors, vec!["Cannot apply operator '-' to object."]);
+    assert_eq!(eval("!0 == true"), "true");
+}
+
+#[test]
+fn custom_operators_use_their_declared_priority() {
+    assert_eq!(eval("operator <=> (a, b) priority -2 { return a - b; }; 1 + 4 <=> 3"), "2");
+    assert_eq!(eval("operator ** (a, b) priority 1 { return a * b * 10; }; 1 + 2 ** 3"), "61");
+}
+
+#[test]
+fn custom_operators_without_priority_are_reported() {
+    let errors = eval_errors("operator <=> (a, b) priority { return a - b; };");
+    assert_eq!(errors, vec!["Expected 'priority <n>' after the arguments of operator '<=>'."]);
+}
+
+#[test]
+fn syntax_errors_are_all_reported() {
+    assert_eq!(eval_errors("let = ; let x = (1; let y = 2 +;").len(), 3);
+    assert_eq!(eval_errors("let o = { a: 1 b: 2 }; let = ;").len(), 2);
+    assert_eq!(eval_errors("if (1) { let = ; } else { let z = ; }").len(), 2);
+    assert_eq!(eval_errors("try { let = ; } catch (e) { let z = ; } finally { let w = ; }").len(), 3);
+}
+
+#[test]
+fn for_of_needs_a_variable_name() {
+    assert_eq!(eval_errors("for (let 5 of [1]) {}"), vec!["Expected variable name."]);
+}
+
+#[test]
+fn for_loops_give_each_iteration_its_own_variables() {
+    let source = "let fs = []; for (let i = 0; i < 3; i += 1) { fs.push(function() { return i; }); } fs[0]() + fs[2]()";
+    assert_eq!(eval(source), "2");
+}
+
+#[test]
+fn closures_keep_the_variables_of_their_scope() {
+    let source = "let make_counter = function() { let count = 0; return function() { count += 1; return count; }; };
+        let a = make_counter(); let b = make_counter(); a(); a(); b(); a()";
+    assert_eq!(eval(source), "3");
+}
+
+#[test]
+fn objects_are_shared_by_assignment() {
+    assert_eq!(eval("let a = { x: 1 }; let b = a; b.x = 2; b.y = 3; a"), "object { x : 2, y : 3 }");
+    assert_eq!(eval("let a = { x: 1 }; let f = function(o) { delete o.x; }; f(a); a"), "object { }");
+    assert_eq!(eval("{ x: 1, y: { z: 2 } } == { y: { z: 2 }, x: 1 }"), "true");
+}
+
+#[test]
+fn values_containing_themselves_can_be_dumped_and_compared() {
+    assert_eq!(eval("let a = [1]; a.push(a); a"), "[1, [...]]");
+    assert_eq!(eval("let o = { x: 1 }; o.self = o; o"), "object { x : 1, self : object {...} }");
+    assert_eq!(eval("let o = {}; o.self = o; let p = {}; p.self = p; o == p"), "true");
+}
+
+#[test]
+fn arrays_cannot_grow_too_far_past_their_end() {
+    assert_eq!(eval("let a = [1]; a[3] = 4; a"), "[1, Undefined, Undefined, 4]");
+    let errors = eval_errors("let a = [1]; a[1000000000000] = 3;");
+    assert_eq!(errors, vec!["Cannot assign element 1000000000000 of an array of length 1, at most 65536 elements past the end."]);
+}
+
+#[test]
+fn calling_undefined_is_a_catchable_error() {
+    assert_eq!(eval("let m = 0; try { undefinedfn(); } catch (e) { m = e.message; } m"), "Cannot call undefined.");
+}
+
+#[test]
+fn deep_recursion_is_a_catchable_error() {
+    let source = "let f = function(n) { if (n == 0) { return 0; } return 1 + f(n - 1); };
+        let m = 0; try { f(100000); } catch (e) { m = e.message; } m";
+    assert!(eval(source).starts_with("Stack overflow"));
+
+    let mut interpreter = Interpreter::new();
+    interpreter.set_max_call_depth(10);
+    let result = interpreter.eval("let f = function(n) { if (n == 0) { return 0; } return 1 + f(n - 1); }; f(20);");
+    match result {
+        Err(Error::Runtime(error)) => assert_eq!(error.diagnostic.message, "Stack overflow, more than 10 nested calls."),
+        _ => panic!("the recursion didn't fail")
+    }
+}
+
+#[test]
+fn recursion_hundreds_of_calls_deep_runs_with_the_default_limits() {
+    // The size of the stack of a main thread, tests running on smaller ones.
+    let thread = std::thread::Builder::new().stack_size(8 * 1024 * 1024).spawn(|| {
+        eval("let f = function(n) { if (n == 0) { return 0; } return 1 + f(n - 1); }; f(400)")
+    });
+    assert_eq!(thread.unwrap().join().unwrap(), "400");
+}
+
+#[test]
+fn deeply_nested_expressions_are_a_catchable_error() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_max_stack_size(256 * 1024);
+    let source = format!("let m = 0; try {{ let x = {}; }} catch (e) {{ m = e.message; }} m", vec!["1"; 2000].join(" + "));
+    let result = interpreter.eval(source.as_str()).unwrap().unwrap();
+    assert_eq!(result.borrow().get_value_dump(), "Stack overflow, more than 262144 bytes of stack used.");
+}
+
+#[test]
+fn columns_count_characters() {
+    let mut interpreter = Interpreter::new();
+    let tokens = interpreter.tokenize("<test>", "let é = 'ü';\n  é");
+    let positions: Vec<(usize, usize)> = tokens.iter().map(|token| (token.span.line, token.span.column)).collect();
+    assert_eq!(positions, vec![(1, 1), (1, 5), (1, 7), (1, 9), (1, 12), (2, 3)]);
+}
+
+#[test]
+fn global_functions_are_freed_with_the_interpreter() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("let f = function() { return 1; };").unwrap();
+    let environment = std::rc::Rc::downgrade(&interpreter.get_global_environment());
+    drop(interpreter);
+    assert!(environment.upgrade().is_none());
+}
+
+#[test]
+fn strings_are_never_keywords() {
+    assert_eq!(eval("\"return\"; \"let\"; 1"), "1");
+}
+
+#[test]
+fn not_equal_falls_back_to_the_equal_overload() {
+    let point = "let p = { x: 1, __eq__: function(l, r) { return l.x == r.x; } };";
+    assert_eq!(eval(format!("{} p != {{ x: 1 }}", point).as_str()), "false");
+    assert_eq!(eval(format!("{} p != {{ x: 2 }}", point).as_str()), "true");
+    assert_eq!(eval("let p = { __ne__: function(l, r) { return 5; } }; p != p"), "5");
+}
+
+#[test]
+fn iterators_must_return_objects() {
+    let source = "let it = { next: function() { return 1; } }; for (let x of it) {}";
+    assert_eq!(eval_errors(source), vec!["Iterator result is not an object."]);
+}
+
+#[test]
+fn custom_operators_are_resolved_where_they_are_used_in_the_source() {
+    let declare = "let make = function() { operator <=> (a, b) priority 0 { return 42; }; return function() { return 1 <=> 2; }; };";
+    assert_eq!(eval(format!("{} make()()", declare).as_str()), "42");
+    let source = "let f = function() { return 1 <=> 2; };
+        let g = function() { operator <=> (a, b) priority 0 { return 42; }; return f(); }; g();";
+    assert_eq!(eval_errors(source), vec!["Operator '<=>' has no implementation in this scope."]);
+}
+
+#[test]
+fn sources_that_fail_to_parse_declare_no_operators() {
+    let mut interpreter = Interpreter::new();
+    assert!(interpreter.eval("operator <=> (a, b) priority 0 { return 1; }; let = ;").is_err());
+    assert_eq!(interpreter.tokenize("<test>", "1 <=> 2").len(), 4);
+}
+
+#[test]
+fn deeply_nested_sources_are_a_syntax_error() {
+    let sources = [
+        format!("{}1{}", "(".repeat(5000), ")".repeat(5000)),
+        format!("let a = 0; {}1;", "-".repeat(20000)),
+        format!("let a = 0; {}1;", "a = ".repeat(5000)),
+        format!("{}{}", "if (1) { ".repeat(5000), "}".repeat(5000)),
+    ];
+    for source in sources.iter() {
+        assert_eq!(eval_errors(source.as_str()), vec!["Source nested too deeply."]);
+    }
+}
+
+#[test]
+fn long_expressions_are_dropped_without_nested_calls() {
+    let mut interpreter = Interpreter::new();
+    let source = vec!["1"; 100000].join(" + ");
+    let tokens = interpreter.tokenize("<test>", source.as_str());
+    assert!(interpreter.parse(&tokens).is_ok());
+}
+
+#[test]
+fn cycles_between_functions_environments_and_containers_are_collected() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter.eval("let o = { a: [] }; o.a[0] = o; o.self = o; o").ok().flatten().unwrap();
+    let members = match &result.borrow().constant {
+        Some(Constant::Object(members)) => Rc::downgrade(members),
+        _ => panic!("not an object")
+    };
+    let function = interpreter.eval("let make = function() { let h = function() { return 1; }; return h; }; make()")
+        .ok().flatten().unwrap();
+    let closure = match &function.borrow().constant {
+        Some(Constant::Function(function)) => Rc::downgrade(&function.borrow().get_closure().unwrap()),
+        _ => panic!("not a function")
+    };
+    assert_eq!(eval("let make = function() { let h = function() { return 1; }; return h; }; make()()"), "1");
+    drop(result);
+    drop(function);
+    drop(interpreter);
+    collector::collect();
+    assert!(members.upgrade().is_none());
+    assert!(closure.upgrade().is_none());
+}
+
+#[test]
+fn objects_overload_arithmetic_and_comparison_operators() {
+    let vector = "let v = { x: 6,
+        __add__: function(l, r) { return l.x + r; }, __sub__: function(l, r) { return l.x - r; },
+        __mul__: function(l, r) { return l.x * r; }, __div__: function(l, r) { return l.x / r; },
+        __lt__: function(l, r) { return 'lt'; }, __gt__: function(l, r) { return 'gt'; },
+        __le__: function(l, r) { return 'le'; }, __ge__: function(l, r) { return 'ge'; } };";
+    assert_eq!(eval(format!("{} [v + 2, v - 2, v * 2, v / 2]", vector).as_str()), "[8, 4, 12, 3]");
+    assert_eq!(eval(format!("{} [v < 1, v > 1, v <= 1, v >= 1]", vector).as_str()), "[lt, gt, le, ge]");
+}
+
+#[test]
+fn operators_without_overloads_are_errors() {
+    assert_eq!(eval_errors("let v = { x: 1 }; v + 1;"), vec!["Cannot apply operator '+' to object and integer."]);
+    assert_eq!(eval_errors("let v = { __add__: 3 }; v + 1;"), vec!["Member '__add__' is not a function."]);
+    // Only the left operand is looked at.
+    let errors = eval_errors("let v = { __add__: function(l, r) { return 1; } }; 2 + v;");
+    assert_eq!(errors, vec!["Cannot apply operator '+' to integer and object."]);
+}
+
+#[test]
+fn comparisons_order_numbers_and_strings() {
+    assert_eq!(eval("[1 < 2, 2 < 1, 1 <= 1, 2 >= 3, 1 > 0, 1.5 > 1, 1 < 1.5, 2.0 >= 2]"), "[true, false, true, false, true, true, true, true]");
+    assert_eq!(eval("['a' < 'b', 'b' <= 'a', 'ab' > 'a', 'B' < 'a', '' < 'a']"), "[true, false, true, true, true]");
+}
+
+#[test]
+fn values_of_different_types_are_never_equal() {
+    assert_eq!(eval("[1 == '1', null == undefined, true == 1, 1 != '1', 1 == 1.0]"), "[false, false, false, true, true]");
+    assert_eq!(eval("[null == null, undefined == undefined, 'a' == 'a', 2.5 == 2.5]"), "[true, true, true, true]");
+    assert_eq!(eval("let f = function() {}; let g = function() {}; [f == f, f == g]"), "[true, false]");
+}
+
+#[test]
+fn ordering_against_undefined_is_false_and_other_types_are_errors() {
+    assert_eq!(eval("[undefined < 1, 1 > undefined, undefined >= undefined]"), "[false, false, false]");
+    assert_eq!(eval_errors("'a' < 1;"), vec!["Cannot compare string with integer."]);
+    assert_eq!(eval_errors("null < 1;"), vec!["Cannot compare null with integer."]);
+    assert_eq!(eval_errors("true < false;"), vec!["Cannot compare boolean with boolean."]);
+}
+
+#[test]
+fn constants_of_every_type_are_dumped() {
+    assert_eq!(eval("[true, false, null, undefined, 1, 1.0, 'str']"), "[true, false, null, Undefined, 1, 1.0, str]");
+}
+
+#[test]
+fn mixing_integers_with_floats_gives_floats() {
+    assert_eq!(eval("[1.5 + 1, 2 - 0.5, 3 / 2, 3.0 / 2, 0.1 * 3, 1.0 / 0]"), "[2.5, 1.5, 1, 1.5, 0.30000000000000004, inf]");
+    assert_eq!(eval_errors("1 / 0;"), vec!["Division by zero."]);
+}
+
+#[test]
+fn adding_a_string_concatenates() {
+    assert_eq!(eval("['a' + 1, 1 + 'a', 'a' + 1.5, 'x' + true, 'x' + null, 'a' + 'b']"), "[a1, 1a, a1.5, xtrue, xnull, ab]");
+    assert_eq!(eval_errors("'a' - 1;"), vec!["Cannot apply operator '-' to string and integer."]);
+}
+
+#[test]
+fn if_runs_the_first_branch_with_a_true_condition() {
+    assert_eq!(eval("let r = 0; if (0) { r = 1; } else if ('') { r = 2; } else if (null) { r = 3; } else { r = 4; } r"), "4");
+    assert_eq!(eval("let r = 0; if (0.0) { r = 1; } else if (1) { r = 2; } else { r = 3; } r"), "2");
+    assert_eq!(eval("let r = 0; if ({}) { r = 1; } else { r = 2; } r"), "1");
+    assert_eq!(eval("let r = 0; if ([]) { r = 1; } r"), "1");
+    assert_eq!(eval("let r = 0; if (undefined) { r = 1; } r"), "0");
+}
+
+#[test]
+fn branches_declare_in_their_own_block() {
+    assert_eq!(eval("let x = 1; if (1) { let x = 2; } x"), "1");
+    assert_eq!(eval("let x = 1; if (1) { x = 2; } x"), "2");
+}
+
+#[test]
+fn while_loops_until_the_condition_is_false() {
+    assert_eq!(eval("let i = 0; while (i < 3) { i += 1; } i"), "3");
+    assert_eq!(eval("while (0) { undefinedfn(); } 1"), "1");
+}
+
+#[test]
+fn break_and_continue_apply_to_the_innermost_loop() {
+    let source = "let n = 0; let i = 0; while (i < 10) { i += 1; if (i == 3) { continue; } n += i; if (n > 20) { break; } } [i, n]";
+    assert_eq!(eval(source), "[7, 25]");
+    let source = "let i = 0; let n = 0; while (i < 3) { i += 1; let j = 0; while (true) { j += 1; if (j == 2) { break; } n += 1; } } n";
+    assert_eq!(eval(source), "3");
+    assert_eq!(eval("let f = function() { while (true) { return 7; } }; f()"), "7");
+}
+
+#[test]
+fn break_and_continue_outside_loops_are_errors() {
+    let message = "'break' and 'continue' can only be used inside a loop.";
+    assert_eq!(eval_errors("break;"), vec![message]);
+    assert_eq!(eval_errors("continue;"), vec![message]);
+    // A function body isn't inside the loop it is called from.
+    assert_eq!(eval_errors("let f = function() { break; }; while (true) { f(); }"), vec![message]);
+}
+
+#[test]
+fn errors_keep_the_calls_they_went_through() {
+    let source = "let f = function() { throw Error('boom'); };\nlet g = function() { f(); };\ng();";
+    let mut interpreter = Interpreter::new();
+    match interpreter.eval(source) {
+        Err(Error::Runtime(error)) => {
+            assert_eq!(error.diagnostic.message, "boom");
+            let frames: Vec<(String, usize)> = error.stack.iter()
+                .map(|frame| (frame.function.clone(), frame.call_site.as_ref().unwrap().line))
+                .collect();
+            assert_eq!(frames, vec![("f".to_string(), 2), ("g".to_string(), 3)]);
+        }
+        _ => panic!("the script didn't fail")
+    }
+}
+
+#[test]
+fn caught_errors_hold_their_stack_trace() {
+    let source = "let e = 0; let f = function() { throw Error('boom'); }; let g = function() { f(); };
+        try { g(); } catch (x) { e = [x.message, x.stack]; } e";
+    assert_eq!(eval(source), "[boom, at <eval>:1:33\nin 'f', called at <eval>:1:78\nin 'g', called at <eval>:2:15]");
+    assert_eq!(eval("let e = 0; try { undefinedfn(); } catch (x) { e = x.stack; } e"), "at <eval>:1:18");
+    let source = "let e = 0; let f = function(n) { if (n == 0) { throw Error('deep'); } f(n - 1); };
+        try { f(2); } catch (x) { e = x.stack; } e";
+    assert_eq!(eval(source), "at <eval>:1:48\nin 'f', called at <eval>:1:71\n... the same call 1 more time\nin 'f', called at <eval>:2:15");
+}
+
+#[test]
+fn thrown_values_are_caught_as_they_are() {
+    assert_eq!(eval("let e = 0; try { throw 5; } catch (x) { e = x; } e"), "5");
+    assert_eq!(eval("let m = 0; try { try { throw 'a'; } catch (e) { throw e + 'c'; } } catch (e) { m = e; } m"), "ac");
+    assert_eq!(eval_errors("throw 5;"), vec!["Uncaught exception: 5."]);
+}
+
+#[test]
+fn finally_runs_however_the_block_ends() {
+    let source = "let r = 0; let f = function() { try { throw 1; } catch (e) { return 3; } finally { r = 9; } }; [f(), r]";
+    assert_eq!(eval(source), "[3, 9]");
+    assert_eq!(eval("let i = 0; let n = 0; while (i < 3) { i += 1; try { continue; } finally { n += 1; } } n"), "3");
+    assert_eq!(eval("let i = 0; while (i < 5) { i += 1; try { break; } finally { i = 10; } } i"), "10");
+}
+
+#[test]
+fn finally_overrides_how_the_block_ends() {
+    assert_eq!(eval("let f = function() { try { return 1; } finally { return 2; } }; f()"), "2");
+    assert_eq!(eval("let f = function() { try { throw 1; } finally { return 2; } }; f()"), "2");
+    assert_eq!(eval("let f = function() { while (true) { try { return 1; } finally { break; } } return 5; }; f()"), "5");
+    assert_eq!(eval("let m = 0; try { try { throw 'a'; } finally { throw 'b'; } } catch (e) { m = e; } m"), "b");
+}
+
+#[test]
+fn unary_operators_apply_to_numbers_and_truthiness() {
+    assert_eq!(eval("[-1, - -1, -1.5, +3, +2.5, - (2 + 3)]"), "[-1, 1, -1.5, 3, 2.5, -5]");
+    assert_eq!(eval("[!true, !0, !'', !'a', !null, !undefined, !!1, !{}, ![]]"), "[false, true, true, false, true, true, true, false, false]");
+    assert_eq!(eval("let a = 2; -a * -a"), "4");
+}
+
+#[test]
+fn negating_values_other_than_numbers_is_an_error() {
+    assert_eq!(eval_errors("-'a';"), vec!["Cannot apply operator '-' to string."]);
+    assert_eq!(eval_errors("+true;"), vec!["Cannot apply operator '+' to boolean."]);
+    assert_eq!(eval_errors("-null;"), vec!["Cannot apply operator '-' to null."]);
+}
+
+#[test]
+fn arrays_push_pop_and_slice() {
+    assert_eq!(eval("let a = [1, 2, 3]; [a.push(4, 5), a.length, a.pop(), a.length, a]"), "[5, 5, 5, 4, [1, 2, 3, 4]]");
+    assert_eq!(eval("let a = []; [a.pop(), a.length]"), "[Undefined, 0]");
+    let source = "let a = [1, 2, 3, 4]; [a.slice(1, 3), a.slice(2), a.slice(3, 1), a.slice(0, 100), a]";
+    assert_eq!(eval(source), "[[2, 3], [3, 4], [], [1, 2, 3, 4], [1, 2, 3, 4]]");
+    assert_eq!(eval("let a = [1, 2]; let b = a.slice(0); b.push(3); [a.length, b.length]"), "[2, 3]");
+}
+
+#[test]
+fn arrays_are_indexed_from_zero() {
+    assert_eq!(eval("let a = [[1, 2], [3]]; a[0][1] + a[1][0]"), "5");
+    assert_eq!(eval("let a = [1, 2]; a[5]"), "Undefined");
+    assert_eq!(eval("[1, 2] == [1, 2]"), "false");
+    assert_eq!(eval_errors("let a = [1]; a['x'];"), vec!["No member 'x' in array."]);
+    assert_eq!(eval_errors("let a = [1]; a.length = 3;"), vec!["Cannot add member 'length' to array."]);
+}
+
+#[test]
+fn negative_array_indices_are_errors() {
+    let message = "Array indices cannot be negative, found -1.";
+    assert_eq!(eval_errors("let a = [1]; a[-1];"), vec![message]);
+    assert_eq!(eval_errors("let a = [1]; a[-1] = 2;"), vec![message]);
+    assert_eq!(eval_errors("let a = [1, 2, 3]; a.slice(-1);"), vec![message]);
+}
+
+#[test]
+fn each_call_gets_its_own_variables() {
+    assert_eq!(eval("let count = function() { let c = 0; c += 1; return c; }; count(); count()"), "1");
+    assert_eq!(eval("let f = function(n) { let local = n; if (n > 0) { f(n - 1); } return local; }; f(3)"), "3");
+    assert_eq!(eval("let g = 1; let f = function() { g = 2; let g = 3; return g; }; [f(), g]"), "[3, 2]");
+    assert_eq!(eval("let x = 1; let f = function() { return x; }; let g = function() { let x = 2; return f(); }; g()"), "1");
+}
+
+#[test]
+fn parsed_scopes_can_be_executed_again() {
+    let mut interpreter = Interpreter::new();
+    let tokens = interpreter.tokenize("<test>", "let n = 0; let f = function() { let m = 1; n += m; return n; }; f(); f()");
+    let scope = interpreter.parse(&tokens).ok().unwrap();
+    for _ in 0..2 {
+        let result = interpreter.execute(&scope).ok().flatten().unwrap();
+        assert_eq!(result.borrow().get_value_dump(), "2");
+    }
+}